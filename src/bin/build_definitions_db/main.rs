@@ -0,0 +1,1004 @@
+extern crate define3;
+extern crate regex;
+extern crate rusqlite;
+extern crate getopts;
+
+mod progress;
+
+use define3::datasets::{self, cedict, jmdict, wordnet};
+use define3::dictionary::{create_indexes, create_tables, tune_for_building, Batch};
+use define3::editions::{self, Edition};
+use define3::{Dictionary, Error, Module, PageRef, Redirect, Result, Template, Word};
+use define3::PageContent;
+use define3::error::SqlStep;
+use define3::languages;
+use define3::parse_wikitext::{definition_links, parse_wikitext};
+
+use getopts::{Matches, Options};
+use progress::Progress;
+use regex::Regex;
+use rusqlite::{Connection, OptionalExtension, Transaction};
+use std::collections::HashMap;
+use std::fs::File;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+// Tables with a row per page of a source, which an update replaces. Deleting an entry deletes its
+// senses, relations and links too.
+const PAGE_TABLES: &[&str] = &["entries", "redirects", "templates", "modules"];
+
+fn io_error<P: AsRef<Path>>(path: P) -> impl FnOnce(io::Error) -> Error {
+    let path = path.as_ref().to_owned();
+    move |source| Error::Io { path, source }
+}
+
+// Writes every word in the database to `out_path` as wiktextract-style JSON, one entry per line.
+fn export_jsonl(dict: &Dictionary, out_path: &str) -> Result<()> {
+    let mut out = BufWriter::new(File::create(out_path).map_err(io_error(out_path))?);
+    let mut write_result = Ok(());
+    dict.for_each_word(|word| {
+        for entry in define3::wiktextract::entries(&word) {
+            if write_result.is_ok() {
+                write_result = writeln!(out, "{}", entry);
+            }
+        }
+    })?;
+    write_result
+        .and_then(|_| out.flush())
+        .map_err(io_error(out_path))
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "print this help text");
+    opts.optopt("", "export-jsonl", "instead of building, export the database as wiktextract-style JSON lines", "OUT");
+    opts.optopt("", "update", "instead of building, apply an adds-changes dump to the existing database", "INCR.xml");
+    opts.optflag("", "resume", "continue an interrupted build of the same dump");
+    opts.optflag("", "append", "add the dump to the existing database, replacing what it had from the same edition");
+    opts.optflag("q", "quiet", "only print errors and warnings, without progress");
+    opts.optflag("", "strict", "stop at the first malformed page instead of skipping it");
+    let codes: Vec<&str> = editions::EDITIONS.iter().map(|edition| edition.code).collect();
+    opts.optopt(
+        "",
+        "edition",
+        &format!("which Wiktionary the dump is from: {} (defaults to en)", codes.join(", ")),
+        "CODE",
+    );
+    let brief = format!(
+        "Usage: {0} PATH_TO_enwiktionary-YYYYMMDD-pages-meta-current.xml [options]\n       \
+         {0} import FORMAT FILE [options]\n\n\
+         Dumps have to be extracted first; compressed .bz2 files aren't read.\n\
+         import adds another dictionary to the existing database. FORMAT is one of {1}.",
+        args[0],
+        datasets::FORMATS.join(", ")
+    );
+    let matches = match opts.parse(&args[1..]) {
+        Ok(matches) => matches,
+        Err(e) => {
+            eprintln!("{}: {}", args[0], e);
+            eprint!("{}", opts.usage(&brief));
+            process::exit(2);
+        }
+    };
+    let importing = matches.free.first().map(String::as_str) == Some("import");
+    let building = !importing && !["export-jsonl", "update"].iter().any(|&name| matches.opt_present(name));
+    if matches.opt_present("h") || (building && matches.free.len() != 1) || (importing && matches.free.len() != 3) {
+        print!("{}", opts.usage(&brief));
+        return;
+    }
+
+    if let Err(e) = run(&matches) {
+        eprintln!("{}: {}", args[0], e);
+        process::exit(1);
+    }
+}
+
+fn run(matches: &Matches) -> Result<()> {
+    let mut sqlite_path = dirs::data_dir().ok_or(Error::NoDataDir)?;
+    sqlite_path.push("define3");
+    fs::create_dir_all(&sqlite_path).map_err(io_error(&sqlite_path))?;
+    sqlite_path.push("define3.sqlite3");
+
+    if let Some(out_path) = matches.opt_str("export-jsonl") {
+        let dict = Dictionary::open(&sqlite_path)?;
+        return export_jsonl(&dict, &out_path);
+    }
+    let quiet = matches.opt_present("quiet");
+    if matches.free.first().map(String::as_str) == Some("import") {
+        let path = &matches.free[2];
+        return match matches.free[1].as_str() {
+            jmdict::SOURCE => import(&sqlite_path, jmdict::SOURCE, path, |path| jmdict::read(path), quiet),
+            cedict::SOURCE => import(&sqlite_path, cedict::SOURCE, path, |path| cedict::read(path), quiet),
+            wordnet::SOURCE => import(&sqlite_path, wordnet::SOURCE, path, |path| wordnet::read(path), quiet),
+            format => Err(Error::UnknownFormat(format.to_owned())),
+        };
+    }
+    let edition = match matches.opt_str("edition") {
+        Some(code) => Some(Edition::find(&code).ok_or(Error::UnknownEdition(code))?),
+        None => None,
+    };
+    if let Some(xml_path) = matches.opt_str("update") {
+        return update(
+            &xml_path,
+            &sqlite_path,
+            edition,
+            matches.opt_present("strict"),
+            quiet,
+        );
+    }
+    build(
+        &matches.free[0],
+        &sqlite_path,
+        edition,
+        matches.opt_present("strict"),
+        matches.opt_present("resume"),
+        matches.opt_present("append"),
+        quiet,
+    )
+}
+
+// Records the languages described by the saved language data modules, so define can look them up
+// by code or by another name. Languages keep their ids, since definitions refer to them.
+fn save_languages(conn: &Connection) -> Result<usize> {
+    let modules = {
+        let mut stmt = conn
+            .prepare("SELECT name, content FROM modules WHERE name LIKE 'languages/data%'")
+            .step("reading the language data modules")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .step("reading the language data modules")?;
+        rows.collect::<rusqlite::Result<HashMap<String, String>>>()
+            .step("reading the language data modules")?
+    };
+
+    conn.execute_batch(
+        "UPDATE languages SET code = NULL, family = NULL, scripts = NULL;
+         DELETE FROM language_aliases;",
+    ).step("clearing old language data")?;
+
+    let mut count = 0;
+    for (name, src) in &modules {
+        if !languages::is_data_module(name) {
+            continue;
+        }
+        for language in languages::parse_module(src) {
+            conn.execute(
+                "insert into languages (canonical_name, code, family, scripts)
+         values (?1, ?2, ?3, ?4)
+         on conflict (canonical_name) do update
+         set code = excluded.code, family = excluded.family, scripts = excluded.scripts",
+                rusqlite::params![
+                    &language.canonical_name,
+                    &language.code,
+                    &language.family,
+                    &language.scripts.join(","),
+                ],
+            ).step("saving a language")?;
+            for alias in &language.aliases {
+                conn.execute(
+                    "insert into language_aliases (alias, language_id)
+             select ?1, id from languages where canonical_name = ?2",
+                    [alias, &language.canonical_name],
+                ).step("saving a language alias")?;
+            }
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+// Records facts about the database in the meta table. `None` leaves a value as it was.
+fn save_meta(conn: &Connection, key: &str, value: Option<&str>) -> Result<()> {
+    if let Some(value) = value {
+        conn.execute(
+            "insert or replace into meta (key, value) values (?1, ?2)",
+            [key, value],
+        ).step("saving metadata")?;
+    }
+    Ok(())
+}
+
+// Records a source the database has definitions from, returning its id. Adding it again keeps
+// its id, so that it can replace what it had before.
+fn save_source(conn: &Connection, name: &str, edition: Option<&str>, build_options: &str) -> Result<i64> {
+    conn.execute(
+        "insert into sources (name, edition, build_options) values (?1, ?2, ?3)
+         on conflict (name) do update set edition = excluded.edition, build_options = excluded.build_options",
+        rusqlite::params![name, edition, build_options],
+    ).step("saving the source")?;
+    conn.query_row("select id from sources where name = ?1", [name], |row| row.get(0))
+        .step("saving the source")
+}
+
+// Records the date of the dump a source was last built or updated from. `None` leaves it as it was.
+fn save_dump_date(conn: &Connection, source_id: i64, date: Option<String>) -> Result<()> {
+    conn.execute(
+        "update sources set dump_date = coalesce(?2, dump_date) where id = ?1",
+        rusqlite::params![source_id, date],
+    ).step("saving the dump date")?;
+    Ok(())
+}
+
+// Deletes everything the database has from the source `name`, if it has any.
+fn clear_source(conn: &Connection, name: &str) -> Result<()> {
+    for table in PAGE_TABLES {
+        conn.execute(
+            &format!("DELETE FROM {} WHERE source_id = (SELECT id FROM sources WHERE name = ?1)", table),
+            [name],
+        ).step("deleting the old definitions")?;
+    }
+    Ok(())
+}
+
+// Finds the date in a dump's file name, like `enwiktionary-20240101-pages-meta-current.xml`.
+fn dump_date(xml_path: &str) -> Option<String> {
+    let file_name = Path::new(xml_path).file_name()?.to_str()?;
+    let captures = Regex::new(r"-(\d{4})(\d{2})(\d{2})-").unwrap().captures(file_name)?;
+    Some(format!("{}-{}-{}", &captures[1], &captures[2], &captures[3]))
+}
+
+// Finds the id of a language or part of speech heading in `table`, adding it if it's new.
+fn heading_id(
+    conn: &Connection,
+    cache: &mut HashMap<String, i64>,
+    table: &str,
+    column: &str,
+    name: &str,
+) -> Result<i64> {
+    if let Some(&id) = cache.get(name) {
+        return Ok(id);
+    }
+    conn.prepare_cached(&format!(
+        "insert into {0} ({1}) values (?1) on conflict ({1}) do nothing",
+        table, column
+    ))
+    .and_then(|mut stmt| stmt.execute([name]))
+    .step("saving a heading")?;
+    let id = conn
+        .prepare_cached(&format!("select id from {} where {} = ?1", table, column))
+        .and_then(|mut stmt| stmt.query_row([name], |row| row.get(0)))
+        .step("saving a heading")?;
+    cache.insert(name.to_owned(), id);
+    Ok(id)
+}
+
+fn in_namespace(title: &str, namespace: &str) -> bool {
+    title.strip_prefix(namespace).is_some_and(|name| name.starts_with(':'))
+}
+
+// A template or module's name, without the `Template:` or `Module:` in its title.
+fn without_namespace(title: &str) -> &str {
+    title.split_once(':').map_or(title, |(_, name)| name)
+}
+
+// Turns pages into rows.
+struct Saver {
+    edition: &'static Edition,
+    source_id: i64,
+    re_noinclude: Regex,
+    re_includeonly: Regex,
+    re_html_comment: Regex,
+    language_ids: HashMap<String, i64>,
+    pos_ids: HashMap<String, i64>,
+    senses: Batch,
+    links: Batch,
+    relations: Batch,
+}
+
+impl Saver {
+    fn new(edition: &'static Edition, source_id: i64) -> Saver {
+        Saver {
+            edition,
+            source_id,
+            re_noinclude: Regex::new(r"<noinclude>(?P<text>(?s:.)*?)</noinclude>").unwrap(),
+            re_includeonly: Regex::new(r"<includeonly>(?P<text>(?s:.)*?)</includeonly>").unwrap(),
+            re_html_comment: Regex::new(r"<!--(?s:.)*?-->").unwrap(),
+            language_ids: HashMap::new(),
+            pos_ids: HashMap::new(),
+            senses: Batch::new(
+                "senses",
+                &["entry_id", "language_id", "pos_id", "definition"],
+                "saving definitions",
+            ),
+            links: Batch::new(
+                "links",
+                &["entry_id", "language_id", "target", "anchor", "text"],
+                "saving links",
+            ),
+            relations: Batch::new(
+                "relations",
+                &["entry_id", "language_id", "relation", "target", "sense"],
+                "saving relations",
+            ),
+        }
+    }
+
+    /// Inserts the rows still waiting in batches. Has to be called before committing, and
+    /// before deleting entries.
+    fn flush(&mut self, conn: &Connection) -> Result<()> {
+        self.senses.flush(conn)?;
+        self.links.flush(conn)?;
+        self.relations.flush(conn)
+    }
+
+    // Links, bold and italics are kept, so that define can render them.
+    fn clean_wikitext(&self, text: &str) -> String {
+        self.re_html_comment.replace_all(text, "").into_owned()
+    }
+
+    fn page_content(&self, page: PageRef) -> PageContent {
+        match page.title.split(':').next() {
+            Some(namespace) if namespace == self.edition.template_namespace => PageContent::Template(Template {
+                name: page.title.into_owned(),
+                content: page.content.into_owned(),
+            }),
+            Some(namespace) if namespace == self.edition.module_namespace => PageContent::Module(Module {
+                name: page.title.into_owned(),
+                src: page.content.into_owned(),
+            }),
+            _ if page.redirect.is_some() => PageContent::Redirect(Redirect {
+                title: page.title.into_owned(),
+                target: page.redirect.unwrap(),
+            }),
+            _ => {
+                let (meanings, relations) =
+                    parse_wikitext(&page.content, self.edition);
+                PageContent::Word(Word {
+                    name: page.title.into_owned(),
+                    meanings,
+                    relations,
+                })
+            }
+        }
+    }
+
+    // Deletes what an earlier attempt at a build saved from a page in `table`. Builds don't
+    // journal, so an interrupted one can leave pages from after its last checkpoint behind.
+    fn replace_page(&self, conn: &Connection, table: &str, page_id: Option<i64>) -> Result<()> {
+        if let Some(page_id) = page_id {
+            conn.prepare_cached(&format!("delete from {} where page_id = ?1 and source_id = ?2", table))
+                .and_then(|mut stmt| stmt.execute([page_id, self.source_id]))
+                .step("replacing a page")?;
+        }
+        Ok(())
+    }
+
+    fn save_template(&self, conn: &Connection, template: &Template, ids: (Option<i64>, Option<i64>)) -> Result<()> {
+        let content = self.re_noinclude.replace_all(&template.content, "");
+        let content = self.re_html_comment.replace_all(&content, "");
+        let content = match self.re_includeonly.captures(&content) {
+            None => content.clone().into_owned(),
+            Some(captures) => captures.name("text").unwrap().as_str().to_owned(),
+        };
+        self.replace_page(conn, "templates", ids.0)?;
+        conn.prepare_cached(
+            "insert into templates (source_id, name, content, page_id, revision_id) values (?1, ?2, ?3, ?4, ?5)",
+        )
+        .and_then(|mut stmt| {
+            stmt.execute(rusqlite::params![self.source_id, without_namespace(&template.name), &content, ids.0, ids.1])
+        })
+            .step("saving a template")?;
+        Ok(())
+    }
+
+    fn save_module(&self, conn: &Connection, module: &Module, ids: (Option<i64>, Option<i64>)) -> Result<()> {
+        self.replace_page(conn, "modules", ids.0)?;
+        conn.prepare_cached(
+            "insert into modules (source_id, name, content, page_id, revision_id) values (?1, ?2, ?3, ?4, ?5)",
+        )
+        .and_then(|mut stmt| {
+            stmt.execute(rusqlite::params![self.source_id, without_namespace(&module.name), &module.src, ids.0, ids.1])
+        })
+        .step("saving a module")?;
+        Ok(())
+    }
+
+    fn save_redirect(&self, conn: &Connection, redirect: &Redirect, ids: (Option<i64>, Option<i64>)) -> Result<()> {
+        conn.prepare_cached(
+            "insert or replace into redirects (source_id, title, target, page_id, revision_id)
+             values (?1, ?2, ?3, ?4, ?5)",
+        )
+        .and_then(|mut stmt| {
+            stmt.execute(rusqlite::params![self.source_id, &redirect.title, &redirect.target, ids.0, ids.1])
+        })
+        .step("saving a redirect")?;
+        Ok(())
+    }
+
+    fn save_word(&mut self, conn: &Connection, word: &Word, ids: (Option<i64>, Option<i64>)) -> Result<()> {
+        if word.meanings.is_empty() && word.relations.is_empty() {
+            return Ok(());
+        }
+        // a page that was moved here replaces what was here before
+        let existing: Option<i64> = conn
+            .prepare_cached("select id from entries where source_id = ?1 and name = ?2")
+            .and_then(|mut stmt| {
+                stmt.query_row(rusqlite::params![self.source_id, &word.name], |row| row.get(0)).optional()
+            })
+            .step("replacing an entry")?;
+        if let Some(existing) = existing {
+            self.flush(conn)?;
+            conn.prepare_cached("delete from entries where id = ?1")
+                .and_then(|mut stmt| stmt.execute([existing]))
+                .step("replacing an entry")?;
+        }
+        let entry_id = conn
+            .prepare_cached("insert into entries (source_id, name, page_id, revision_id) values (?1, ?2, ?3, ?4)")
+            .and_then(|mut stmt| stmt.insert(rusqlite::params![self.source_id, &word.name, ids.0, ids.1]))
+            .step("saving an entry")?;
+
+        for meaning in &word.meanings {
+            let definition = self.clean_wikitext(&meaning.definition);
+            let language_id = heading_id(conn, &mut self.language_ids, "languages", "canonical_name", &meaning.language)?;
+            let pos_id = heading_id(conn, &mut self.pos_ids, "pos", "name", &meaning.part_of_speech)?;
+            for link in definition_links(&meaning.language, &definition) {
+                self.links.push(
+                    conn,
+                    vec![
+                        entry_id.into(),
+                        language_id.into(),
+                        link.target.into(),
+                        link.anchor.into(),
+                        link.text.into(),
+                    ],
+                )?;
+            }
+            self.senses.push(
+                conn,
+                vec![entry_id.into(), language_id.into(), pos_id.into(), definition.into()],
+            )?;
+        }
+        for relation in &word.relations {
+            let language_id = heading_id(conn, &mut self.language_ids, "languages", "canonical_name", &relation.language)?;
+            let sense = relation.sense.as_ref().map(|sense| self.clean_wikitext(sense));
+            self.relations.push(
+                conn,
+                vec![
+                    entry_id.into(),
+                    language_id.into(),
+                    relation.relation.clone().into(),
+                    relation.target.clone().into(),
+                    sense.into(),
+                ],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Malformed pages are skipped unless we're being strict, reporting them if `report` is set.
+fn check_page<'a>(
+    page: Result<PageRef<'a>>,
+    strict: bool,
+    report: bool,
+    progress: &mut Progress,
+) -> Result<Option<PageRef<'a>>> {
+    match page {
+        Ok(page) => Ok(Some(page)),
+        Err(e @ Error::MalformedPage { .. }) if !strict => {
+            if report {
+                progress.warn(&format!("Skipping {}", e));
+                progress.skipped += 1;
+            }
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// Pages read between commits during a build. Each commit records where to resume from.
+const CHECKPOINT_PAGES: u64 = 10000;
+
+// Where a build writes the database, which replaces the one at `sqlite_path` once it's done.
+fn temp_path(sqlite_path: &Path) -> PathBuf {
+    let mut path = sqlite_path.as_os_str().to_owned();
+    path.push(".tmp");
+    PathBuf::from(path)
+}
+
+// How far a build has got.
+struct Checkpoint {
+    pass: u32,
+    offset: u64,
+    skipped: u64,
+}
+
+fn meta(conn: &Connection, key: &str) -> Result<Option<String>> {
+    conn.query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+        .step("reading metadata")
+}
+
+// The id and edition of the source an interrupted build was adding, which has to be the edition
+// asked for, if any.
+fn checkpoint_source(conn: &Connection, path: &Path, requested: Option<&'static Edition>) -> Result<(i64, &'static Edition)> {
+    let source = meta(conn, "checkpoint_source")?.ok_or_else(|| Error::NoBuildToResume(path.to_owned()))?;
+    let recorded = Edition::of_source(&source).ok_or(Error::UnknownSource(source))?;
+    if let Some(requested) = requested {
+        if requested.code != recorded.code {
+            return Err(Error::EditionMismatch {
+                path: path.to_owned(),
+                recorded: recorded.code.to_owned(),
+                requested: requested.code.to_owned(),
+            });
+        }
+    }
+    let source_id = conn
+        .query_row("SELECT id FROM sources WHERE name = ?1", [recorded.source], |row| row.get(0))
+        .step("reading the source")?;
+    Ok((source_id, recorded))
+}
+
+// The id and edition of the source an update applies to: the edition asked for, or else the only
+// Wiktionary edition in the database, or else English Wiktionary.
+fn update_source(conn: &Connection, requested: Option<&'static Edition>) -> Result<(i64, &'static Edition)> {
+    let mut stmt = conn.prepare("SELECT id, name FROM sources ORDER BY id").step("reading the sources")?;
+    let sources: Vec<(i64, &'static Edition)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get::<_, String>(1)?)))
+        .step("reading the sources")?
+        .collect::<rusqlite::Result<Vec<(i64, String)>>>()
+        .step("reading the sources")?
+        .into_iter()
+        .filter_map(|(id, name)| Edition::of_source(&name).map(|edition| (id, edition)))
+        .collect();
+    let wanted = match (requested, sources.as_slice()) {
+        (Some(edition), _) => edition,
+        (None, [(_, edition)]) => edition,
+        (None, _) => &editions::ENGLISH,
+    };
+    sources
+        .iter()
+        .find(|(_, edition)| edition.code == wanted.code)
+        .cloned()
+        .ok_or_else(|| Error::UnknownSource(wanted.source.to_owned()))
+}
+
+// Commits everything saved so far, recording that the build can go on from `checkpoint`.
+fn save_checkpoint(conn: &Connection, checkpoint: &Checkpoint) -> Result<()> {
+    save_meta(conn, "checkpoint_pass", Some(&checkpoint.pass.to_string()))?;
+    save_meta(conn, "checkpoint_offset", Some(&checkpoint.offset.to_string()))?;
+    save_meta(conn, "checkpoint_skipped", Some(&checkpoint.skipped.to_string()))?;
+    conn.execute_batch("COMMIT; BEGIN EXCLUSIVE").step("committing a checkpoint")
+}
+
+// Finds where the interrupted build in `temp_path` left off, making sure it was reading `dump`.
+fn load_checkpoint(conn: &Connection, temp_path: &Path, dump: &str) -> Result<Checkpoint> {
+    let version = define3::dictionary::schema_version(conn)?;
+    if version != define3::dictionary::SCHEMA_VERSION {
+        return Err(Error::IncompatibleDatabase {
+            path: temp_path.to_owned(),
+            version,
+            expected: define3::dictionary::SCHEMA_VERSION,
+        });
+    }
+    // the write-ahead log keeps crashes from breaking it, but not a failing disk or a stray copy
+    let check: String = conn
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .step("checking the interrupted build")?;
+    if check != "ok" {
+        return Err(Error::DamagedDatabase(temp_path.to_owned()));
+    }
+    let number = |key| -> Result<Option<u64>> { Ok(meta(conn, key)?.and_then(|value| value.parse().ok())) };
+    let (pass, offset) = match (number("checkpoint_pass")?, number("checkpoint_offset")?) {
+        (Some(pass), Some(offset)) => (pass as u32, offset),
+        _ => return Err(Error::NoBuildToResume(temp_path.to_owned())),
+    };
+    match meta(conn, "checkpoint_dump")? {
+        Some(ref expected) if expected == dump => (),
+        expected => {
+            return Err(Error::ResumeMismatch {
+                expected: expected.unwrap_or_default(),
+                found: dump.to_owned(),
+            })
+        }
+    }
+    Ok(Checkpoint {
+        pass,
+        offset,
+        skipped: number("checkpoint_skipped")?.unwrap_or(0),
+    })
+}
+
+// Refuses compressed dumps, which can't be read in passes or resumed at a byte offset.
+fn check_uncompressed(xml_path: &str) -> Result<()> {
+    match Path::new(xml_path).extension().and_then(|extension| extension.to_str()) {
+        Some("bz2") | Some("gz") | Some("7z") => Err(Error::CompressedDump(PathBuf::from(xml_path))),
+        _ => Ok(()),
+    }
+}
+
+// Builds the database in a temporary file, committing every so often so that an interrupted build
+// can be resumed, and replaces the database with it once it's done.
+fn build(
+    xml_path: &str,
+    sqlite_path: &Path,
+    edition: Option<&'static Edition>,
+    strict: bool,
+    resume: bool,
+    append: bool,
+    quiet: bool,
+) -> Result<()> {
+    check_uncompressed(xml_path)?;
+    let temp_path = temp_path(sqlite_path);
+    // the same dump, however it's named on the command line
+    let dump = fs::canonicalize(xml_path).map_err(io_error(xml_path))?;
+    let size = fs::metadata(&dump).map_err(io_error(xml_path))?.len();
+    let dump = dump.to_string_lossy();
+    let mut progress = Progress::new(size, quiet);
+
+    if resume {
+        if !temp_path.exists() {
+            return Err(Error::NoBuildToResume(temp_path));
+        }
+    } else {
+        match fs::remove_file(&temp_path) {
+            Ok(()) => progress.println(&format!("Discarding the interrupted build in {:?}", temp_path)),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(io_error(&temp_path)(e)),
+        }
+        // its write-ahead log would be replayed into the new build
+        for suffix in &["-wal", "-shm"] {
+            let mut path = temp_path.as_os_str().to_owned();
+            path.push(suffix);
+            match fs::remove_file(&path) {
+                Ok(()) => (),
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+                Err(e) => return Err(io_error(&path)(e)),
+            }
+        }
+        if append {
+            if !sqlite_path.exists() {
+                return Err(Error::MissingDatabase(sqlite_path.to_owned()));
+            }
+            progress.println(&format!("Copying {:?} to add to it", sqlite_path));
+            fs::copy(sqlite_path, &temp_path).map_err(io_error(&temp_path))?;
+        }
+    }
+
+    let conn = Connection::open(&temp_path).step("opening the database")?;
+    tune_for_building(&conn)?;
+    // foreign keys can only be turned on outside a transaction
+    conn.execute_batch(
+        "PRAGMA foreign_keys = ON;
+         BEGIN EXCLUSIVE",
+    ).step("starting the transaction")?;
+    conn.set_prepared_statement_cache_capacity(32);
+
+    progress.println(&format!("Saving data to {:?}", sqlite_path));
+
+    let mut saver;
+    let mut checkpoint = if resume {
+        let checkpoint = load_checkpoint(&conn, &temp_path, &dump)?;
+        let (source_id, edition) = checkpoint_source(&conn, &temp_path, edition)?;
+        saver = Saver::new(edition, source_id);
+        progress.println(&format!("Resuming pass {} at byte {}", checkpoint.pass, checkpoint.offset));
+        progress.skipped = checkpoint.skipped;
+        progress.definitions = conn
+            .query_row(
+                "SELECT count(*) FROM senses JOIN entries ON entries.id = senses.entry_id
+                 WHERE entries.source_id = ?1",
+                [source_id],
+                |row| row.get::<_, i64>(0),
+            )
+            .step("counting definitions")? as u64;
+        checkpoint
+    } else {
+        let edition = edition.unwrap_or(&editions::ENGLISH);
+        if append {
+            check_schema_version(&conn, sqlite_path)?;
+            clear_source(&conn, edition.source)?;
+        } else {
+            create_tables(&conn)?;
+        }
+        let build_options = if strict { "--strict" } else { "" };
+        let source_id = save_source(&conn, edition.source, Some(edition.code), build_options)?;
+        saver = Saver::new(edition, source_id);
+        save_meta(&conn, "checkpoint_dump", Some(&dump))?;
+        save_meta(&conn, "checkpoint_source", Some(edition.source))?;
+        Checkpoint {
+            pass: 1,
+            offset: 0,
+            skipped: 0,
+        }
+    };
+
+    if checkpoint.pass == 1 {
+        progress.start("Pass 1", "Collecting templates and modules", checkpoint.offset);
+
+        let mut pages: u64 = 0;
+        // Malformed pages are only reported in pass 2, so that each is reported once.
+        define3::parse_xml::for_pages_from(xml_path, checkpoint.offset, |page, offset| {
+            if let Some(page) = check_page(page, strict, false, &mut progress)? {
+                let ids = (page.id, page.revision_id);
+                if in_namespace(&page.title, saver.edition.template_namespace) {
+                    if let PageContent::Template(template) = saver.page_content(page) {
+                        saver.save_template(&conn, &template, ids)?;
+                    }
+                } else if in_namespace(&page.title, saver.edition.module_namespace) {
+                    if let PageContent::Module(module) = saver.page_content(page) {
+                        saver.save_module(&conn, &module, ids)?;
+                        progress.println(&format!("Saved module: {}", module.name));
+                    }
+                }
+            }
+            pages += 1;
+            progress.page(offset);
+            if pages.is_multiple_of(CHECKPOINT_PAGES) {
+                checkpoint.offset = offset;
+                checkpoint.skipped = progress.skipped;
+                save_checkpoint(&conn, &checkpoint)?;
+            }
+            Ok(())
+        })?;
+        progress.finish();
+
+        let count = save_languages(&conn)?;
+        progress.println(&format!("Found {} languages", count));
+        checkpoint.pass = 2;
+        checkpoint.offset = 0;
+        checkpoint.skipped = progress.skipped;
+        save_checkpoint(&conn, &checkpoint)?;
+    }
+
+    progress.start("Pass 2", "Collecting words", checkpoint.offset);
+
+    let mut pages: u64 = 0;
+    define3::parse_xml::for_pages_from(xml_path, checkpoint.offset, |page, offset| {
+        if let Some(page) = check_page(page, strict, true, &mut progress)? {
+            let ids = (page.id, page.revision_id);
+            match saver.page_content(page) {
+                PageContent::Word(word) => {
+                    saver.save_word(&conn, &word, ids)?;
+                    progress.definitions += word.meanings.len() as u64;
+                }
+                PageContent::Redirect(redirect) => saver.save_redirect(&conn, &redirect, ids)?,
+                _ => (),
+            }
+        }
+        pages += 1;
+        progress.page(offset);
+        if pages.is_multiple_of(CHECKPOINT_PAGES) {
+            saver.flush(&conn)?;
+            checkpoint.offset = offset;
+            checkpoint.skipped = progress.skipped;
+            save_checkpoint(&conn, &checkpoint)?;
+        }
+        Ok(())
+    })?;
+    saver.flush(&conn)?;
+    progress.finish();
+
+    progress.println("Creating indexes");
+    create_indexes(&conn)?;
+
+    save_dump_date(&conn, saver.source_id, dump_date(xml_path))?;
+    conn.execute("DELETE FROM meta WHERE key LIKE 'checkpoint%'", [])
+        .step("removing the checkpoint")?;
+    conn.execute_batch("COMMIT").step("committing")?;
+    progress.println("Optimizing the database");
+    conn.execute_batch("ANALYZE; VACUUM").step("optimizing the database")?;
+    // the finished database is a single file again, which readers don't need to write to
+    conn.query_row("PRAGMA journal_mode = DELETE", [], |_| Ok(()))
+        .step("optimizing the database")?;
+    progress.print_totals();
+    if !quiet {
+        print_summary(&conn, saver.source_id)?;
+    }
+    conn.close().map_err(|(_, source)| Error::Sqlite {
+        step: Some("closing the database"),
+        source,
+    })?;
+    fs::rename(&temp_path, sqlite_path).map_err(io_error(sqlite_path))?;
+
+    if progress.skipped > 0 {
+        progress.println(&format!("Skipped {} malformed pages", progress.skipped));
+    }
+    Ok(())
+}
+
+// Prints how many definitions a source has in each language and part of speech.
+fn print_summary(conn: &Connection, source_id: i64) -> Result<()> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT languages.canonical_name, pos.name, count(*) FROM senses
+             JOIN entries ON entries.id = senses.entry_id
+             JOIN languages ON languages.id = senses.language_id
+             JOIN pos ON pos.id = senses.pos_id
+             WHERE entries.source_id = ?1
+             GROUP BY senses.language_id, senses.pos_id
+             ORDER BY languages.canonical_name, pos.name",
+        )
+        .step("summarizing the definitions")?;
+    let rows = stmt
+        .query_map([source_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .step("summarizing the definitions")?
+        .collect::<rusqlite::Result<Vec<(String, String, i64)>>>()
+        .step("summarizing the definitions")?;
+    let entries: i64 = conn
+        .query_row("SELECT count(*) FROM entries WHERE source_id = ?1", [source_id], |row| row.get(0))
+        .step("summarizing the definitions")?;
+
+    let language_width = rows.iter().map(|row| row.0.chars().count()).max().unwrap_or(0);
+    let pos_width = rows.iter().map(|row| row.1.chars().count()).max().unwrap_or(0);
+    let mut total = 0;
+    for (language, pos, count) in &rows {
+        println!(
+            "  {:language_width$}  {:pos_width$}  {:>9}",
+            language,
+            pos,
+            count,
+            language_width = language_width,
+            pos_width = pos_width
+        );
+        total += count;
+    }
+    println!("Saved {} definitions of {} words", total, entries);
+    Ok(())
+}
+
+// Adds the words `read` finds in the dataset at `path` to the database as `source`, replacing
+// what it had from there.
+fn import<F>(sqlite_path: &Path, source: &str, path: &str, read: F, quiet: bool) -> Result<()>
+where
+    F: FnOnce(&str) -> Result<Vec<Word>>,
+{
+    if !sqlite_path.exists() {
+        return Err(Error::MissingDatabase(sqlite_path.to_owned()));
+    }
+    if !quiet {
+        println!("Reading {}", path);
+    }
+    let words = read(path)?;
+
+    let mut conn = Connection::open(sqlite_path).step("opening the database")?;
+    conn.execute_batch("PRAGMA foreign_keys = ON").step("enabling foreign keys")?;
+    let tx = Transaction::new(&mut conn, rusqlite::TransactionBehavior::Exclusive)
+        .step("starting the transaction")?;
+    check_schema_version(&tx, sqlite_path)?;
+    clear_source(&tx, source)?;
+    let source_id = save_source(&tx, source, None, "")?;
+    // the edition is only used to read wiki pages
+    let mut saver = Saver::new(&editions::ENGLISH, source_id);
+    for word in &words {
+        saver.save_word(&tx, word, (None, None))?;
+    }
+    saver.flush(&tx)?;
+    tx.commit().step("committing")?;
+
+    if !quiet {
+        print_summary(&conn, source_id)?;
+    }
+    Ok(())
+}
+
+// Fails unless the database was made by this version of the builder, or one with the same schema.
+fn check_schema_version(conn: &Connection, path: &Path) -> Result<()> {
+    let version = define3::dictionary::schema_version(conn)?;
+    if version != define3::dictionary::SCHEMA_VERSION {
+        return Err(Error::IncompatibleDatabase {
+            path: path.to_owned(),
+            version,
+            expected: define3::dictionary::SCHEMA_VERSION,
+        });
+    }
+    Ok(())
+}
+
+// Applies an adds-changes dump to the database: each page in it replaces the rows made from an
+// older revision of it, and pages whose text is gone have their rows deleted.
+fn update(
+    xml_path: &str,
+    sqlite_path: &Path,
+    edition: Option<&'static Edition>,
+    strict: bool,
+    quiet: bool,
+) -> Result<()> {
+    check_uncompressed(xml_path)?;
+    if !sqlite_path.exists() {
+        return Err(Error::MissingDatabase(sqlite_path.to_owned()));
+    }
+    let size = fs::metadata(xml_path).map_err(io_error(xml_path))?.len();
+    let mut progress = Progress::new(size, quiet);
+    let mut conn = Connection::open(sqlite_path).step("opening the database")?;
+    // this does nothing inside a transaction
+    conn.execute_batch("PRAGMA foreign_keys = ON").step("enabling foreign keys")?;
+    let tx = Transaction::new(&mut conn, rusqlite::TransactionBehavior::Exclusive)
+        .step("starting the transaction")?;
+
+    check_schema_version(&tx, sqlite_path)?;
+    let (source_id, edition) = update_source(&tx, edition)?;
+    let mut saver = Saver::new(edition, source_id);
+
+    let (mut updated, mut deleted, mut unchanged) = (0u64, 0u64, 0u64);
+    let mut languages_changed = false;
+
+    progress.start("Updating", &format!("{:?}", sqlite_path), 0);
+    define3::parse_xml::for_pages_from(xml_path, 0, |page, offset| {
+        progress.page(offset);
+        let page = match check_page(page, strict, true, &mut progress)? {
+            Some(page) => page,
+            None => return Ok(()),
+        };
+        let page_id = match page.id {
+            Some(page_id) => page_id,
+            None => {
+                progress.warn(&format!("Skipping {}, which has no page id", page.title));
+                progress.skipped += 1;
+                return Ok(());
+            }
+        };
+
+        // the dump can have revisions we've already seen, if updates are applied twice
+        let mut newest: Option<i64> = None;
+        for table in PAGE_TABLES {
+            let revision: Option<i64> = tx
+                .query_row(
+                    &format!("SELECT max(revision_id) FROM {} WHERE page_id = ?1 AND source_id = ?2", table),
+                    [page_id, source_id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .step("looking up a page")?
+                .flatten();
+            newest = newest.max(revision);
+        }
+        let seen = match (newest, page.revision_id) {
+            (Some(newest), Some(revision_id)) => revision_id <= newest,
+            // a deleted page we don't have
+            (None, _) => page.content.is_empty(),
+            _ => false,
+        };
+        if seen {
+            unchanged += 1;
+            return Ok(());
+        }
+
+        for table in PAGE_TABLES {
+            tx.execute(&format!("DELETE FROM {} WHERE page_id = ?1 AND source_id = ?2", table), [page_id, source_id])
+                .step("deleting an old revision")?;
+        }
+        if page.content.is_empty() {
+            deleted += 1;
+        } else {
+            updated += 1;
+        }
+
+        let ids = (page.id, page.revision_id);
+        match saver.page_content(page) {
+            PageContent::Template(template) => saver.save_template(&tx, &template, ids)?,
+            PageContent::Module(module) => {
+                languages_changed |= languages::is_data_module(without_namespace(&module.name));
+                saver.save_module(&tx, &module, ids)?;
+                progress.println(&format!("Saved module: {}", module.name));
+            }
+            PageContent::Word(word) => {
+                saver.save_word(&tx, &word, ids)?;
+                progress.definitions += word.meanings.len() as u64;
+            }
+            PageContent::Redirect(redirect) => saver.save_redirect(&tx, &redirect, ids)?,
+        }
+        // the next page might replace this one
+        saver.flush(&tx)
+    })?;
+    progress.finish();
+
+    if languages_changed {
+        let count = save_languages(&tx)?;
+        progress.println(&format!("Found {} languages", count));
+    }
+    save_dump_date(&tx, source_id, dump_date(xml_path))?;
+
+    tx.commit().step("committing")?;
+    progress.print_totals();
+    progress.println(&format!(
+        "Updated {} pages, deleted {} and left {} unchanged",
+        updated, deleted, unchanged
+    ));
+    if progress.skipped > 0 {
+        progress.println(&format!("Skipped {} malformed pages", progress.skipped));
+    }
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// Shows how far a pass through the dump has got, on a line of stderr that's redrawn every
+/// second, or every half minute when stderr isn't a terminal. Nothing is shown when quiet.
+pub struct Progress {
+    quiet: bool,
+    terminal: bool,
+    size: u64,
+    begun: Instant,
+    label: &'static str,
+    start_offset: u64,
+    offset: u64,
+    started: Instant,
+    shown: Option<Instant>,
+    shown_len: usize,
+    pass_pages: u64,
+    /// The most pages any pass has read, since each pass reads the same dump.
+    pub pages: u64,
+    pub definitions: u64,
+    pub skipped: u64,
+}
+
+fn bytes(n: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut n = n as f64;
+    let mut unit = 0;
+    while n >= 1024.0 && unit + 1 < units.len() {
+        n /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", n)
+    } else {
+        format!("{:.1} {}", n, units[unit])
+    }
+}
+
+fn duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+impl Progress {
+    /// Progress through a dump of `size` bytes.
+    pub fn new(size: u64, quiet: bool) -> Progress {
+        Progress {
+            quiet,
+            terminal: io::stderr().is_terminal(),
+            size,
+            begun: Instant::now(),
+            label: "",
+            start_offset: 0,
+            offset: 0,
+            started: Instant::now(),
+            shown: None,
+            shown_len: 0,
+            pass_pages: 0,
+            pages: 0,
+            definitions: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Starts a pass through the dump from byte `offset`, saying what it's for.
+    pub fn start(&mut self, label: &'static str, description: &str, offset: u64) {
+        self.println(&format!("{}: {}", label, description));
+        self.label = label;
+        self.start_offset = offset;
+        self.offset = offset;
+        self.started = Instant::now();
+        self.shown = Some(self.started);
+        self.pass_pages = 0;
+    }
+
+    /// Counts a page ending at byte `offset`, and shows progress if it's been a while.
+    pub fn page(&mut self, offset: u64) {
+        self.offset = offset;
+        self.pass_pages += 1;
+        self.pages = self.pages.max(self.pass_pages);
+        let interval = Duration::from_secs(if self.terminal { 1 } else { 30 });
+        let now = Instant::now();
+        if self.shown.is_some_and(|shown| now.duration_since(shown) < interval) {
+            return;
+        }
+        self.shown = Some(now);
+        self.show(now);
+    }
+
+    fn show(&mut self, now: Instant) {
+        if self.quiet {
+            return;
+        }
+        let offset = self.offset;
+        let elapsed = now.duration_since(self.started).as_secs_f64();
+        let done = offset.saturating_sub(self.start_offset);
+        let mut line = format!(
+            "{}: {:.1}% of {}, {:.0} pages/s, {} definitions, {} skipped",
+            self.label,
+            100.0 * offset as f64 / self.size.max(1) as f64,
+            bytes(self.size),
+            self.pass_pages as f64 / elapsed.max(0.001),
+            self.definitions,
+            self.skipped,
+        );
+        if done > 0 && offset < self.size {
+            let left = elapsed * self.size.saturating_sub(offset) as f64 / done as f64;
+            line.push_str(&format!(", {} left", duration(Duration::from_secs(left as u64))));
+        }
+        if self.terminal {
+            let padding = " ".repeat(self.shown_len.saturating_sub(line.len()));
+            eprint!("\r{}{}", line, padding);
+            let _ = io::stderr().flush();
+            self.shown_len = line.len();
+        } else {
+            eprintln!("{}", line);
+        }
+    }
+
+    // Clears the progress line, so that something else can be printed.
+    fn clear(&mut self) {
+        if self.shown_len > 0 {
+            eprint!("\r{}\r", " ".repeat(self.shown_len));
+            self.shown_len = 0;
+        }
+    }
+
+    /// Prints a message, unless quiet.
+    pub fn println(&mut self, message: &str) {
+        if !self.quiet {
+            self.clear();
+            println!("{}", message);
+        }
+    }
+
+    /// Prints a warning, even when quiet.
+    pub fn warn(&mut self, message: &str) {
+        self.clear();
+        eprintln!("{}", message);
+    }
+
+    /// Shows the progress at the end of a pass.
+    pub fn finish(&mut self) {
+        self.show(Instant::now());
+        if self.terminal && !self.quiet {
+            eprintln!();
+            self.shown_len = 0;
+        }
+    }
+
+    /// Prints how many pages were read and how long it took, unless quiet.
+    pub fn print_totals(&mut self) {
+        let message = format!("Read {} pages in {}", self.pages, duration(self.begun.elapsed()));
+        self.println(&message);
+    }
+}
@@ -0,0 +1,85 @@
+use colored::Color;
+use std::collections::HashMap;
+use std::env;
+use std::error;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Defaults from `~/.config/define3/config.toml`, like
+///
+/// ```toml
+/// languages = ["en", "ja"]
+/// width = 100
+///
+/// [colors]
+/// language = "blue"
+///
+/// [language_aliases]
+/// jp = "ja"
+/// ```
+///
+/// Command line options take precedence.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Only show these languages unless others are asked for, in this order.
+    pub languages: Vec<String>,
+    /// Show these languages first, in this order. Defaults to `languages`.
+    pub preferred_languages: Vec<String>,
+    /// Never show these languages.
+    pub exclude_languages: Vec<String>,
+    pub width: Option<usize>,
+    pub expand_templates: Option<bool>,
+    pub colors: ColorNames,
+    /// Extra names for languages, mapping to a name, code or alias the dictionary knows.
+    pub language_aliases: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorNames {
+    pub language: Option<String>,
+    pub part_of_speech: Option<String>,
+    pub link: Option<String>,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/define3/config.toml`, or `~/.config/define3/config.toml`, on every
+    /// platform.
+    pub fn path() -> Option<PathBuf> {
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| dirs::home_dir().map(|home| home.join(".config")));
+        config_home.map(|mut path| {
+            path.push("define3");
+            path.push("config.toml");
+            path
+        })
+    }
+
+    /// Reads the config file, if there is one.
+    pub fn load() -> Result<Config, Box<dyn error::Error>> {
+        let path = match Config::path() {
+            Some(path) => path,
+            None => return Ok(Config::default()),
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(format!("{}: {}", path.display(), e).into()),
+        };
+        toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+}
+
+/// Parses a color name like "green" or "bright blue".
+pub fn color(name: &Option<String>, default: Color) -> Result<Color, Box<dyn error::Error>> {
+    match *name {
+        Some(ref name) => name
+            .parse()
+            .map_err(|_| format!("unknown color \"{}\" in the config file", name).into()),
+        None => Ok(default),
+    }
+}
@@ -0,0 +1,452 @@
+extern crate colored;
+extern crate crossterm;
+extern crate define3;
+extern crate getopts;
+extern crate rustyline;
+#[macro_use]
+extern crate serde;
+extern crate textwrap;
+extern crate toml;
+extern crate nom;
+
+mod config;
+mod repl;
+mod tui;
+
+use define3::render::{self, Renderer};
+use define3::templates::Expander;
+use define3::{markup, Dictionary, Error, Filters, Relation, Word};
+
+use colored::*;
+use config::Config;
+use getopts::{Matches, Options};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::error;
+use std::io::{self, IsTerminal};
+use std::process;
+
+type Definitions = BTreeMap<String, BTreeMap<String, Vec<String>>>;
+// language -> relation -> (sense, target)
+type Relations = BTreeMap<String, BTreeMap<String, Vec<(Option<String>, String)>>>;
+
+fn get_defns_by_lang(word: &Word) -> Box<Definitions> {
+    let mut langs: Definitions = BTreeMap::new();
+
+    for meaning in &word.meanings {
+        langs
+            .entry(meaning.language.clone())
+            .or_default()
+            .entry(meaning.part_of_speech.clone())
+            .or_default()
+            .push(meaning.definition.clone());
+    }
+    Box::new(langs)
+}
+
+fn get_relations_by_lang(relations: &[Relation]) -> Box<Relations> {
+    let mut langs: Relations = BTreeMap::new();
+
+    for relation in relations {
+        let targets = langs
+            .entry(relation.language.clone())
+            .or_default()
+            .entry(relation.relation.clone())
+            .or_default();
+        let entry = (relation.sense.clone(), relation.target.clone());
+        if !targets.contains(&entry) {
+            targets.push(entry);
+        }
+    }
+    Box::new(langs)
+}
+
+fn relation_label(relation: &str) -> &str {
+    match relation {
+        "synonym" => "Synonyms",
+        "antonym" => "Antonyms",
+        "hypernym" => "Hypernyms",
+        "hyponym" => "Hyponyms",
+        "meronym" => "Meronyms",
+        "holonym" => "Holonyms",
+        "troponym" => "Troponyms",
+        "coordinate" => "Coordinate terms",
+        "derived" => "Derived terms",
+        "related" => "Related terms",
+        _ => relation,
+    }
+}
+
+/// A key to sort languages by, which puts `preferred` languages first, in that order, and the
+/// rest alphabetically.
+pub fn language_order(lang: &str, preferred: &[String]) -> (usize, String) {
+    let rank = preferred.iter().position(|p| p == lang).unwrap_or(preferred.len());
+    (rank, lang.to_owned())
+}
+
+fn print_words<F>(
+    langs: &Definitions,
+    relations: &Relations,
+    settings: &Settings,
+    renderer: &dyn Renderer,
+    mut expand: F,
+) where
+    F: FnMut(&str) -> String,
+{
+    let empty_poses = BTreeMap::new();
+    let empty_relations = BTreeMap::new();
+    let mut all_langs: Vec<&String> = langs.keys().chain(relations.keys()).collect();
+    all_langs.sort_by_key(|lang| language_order(lang, &settings.preferred_languages));
+    all_langs.dedup();
+
+    for lang in &all_langs {
+        print!("{}", renderer.language(lang));
+        for (pos, defns) in langs.get(*lang).unwrap_or(&empty_poses) {
+            let defns: Vec<String> = defns.iter().map(|defn| renderer.inline(&expand(defn))).collect();
+            print!("{}", renderer.section(pos, &defns, true));
+        }
+        for (relation, targets) in relations.get(*lang).unwrap_or(&empty_relations) {
+            // one line per sense, in the order the senses first appear
+            let mut senses: Vec<(&Option<String>, Vec<String>)> = Vec::new();
+            for (sense, target) in targets {
+                let target = renderer.link(target, Some(lang), target);
+                match senses.iter_mut().find(|(s, _)| *s == sense) {
+                    Some((_, words)) => words.push(target),
+                    None => senses.push((sense, vec![target])),
+                }
+            }
+            let lines: Vec<String> = senses
+                .into_iter()
+                .map(|(sense, words)| match *sense {
+                    Some(ref sense) => {
+                        format!("({}) {}", renderer.inline(&expand(sense)), words.join(", "))
+                    }
+                    None => words.join(", "),
+                })
+                .collect();
+            print!("{}", renderer.section(relation_label(relation), &lines, false));
+        }
+    }
+
+    if all_langs.is_empty() {
+        println!("No results found.");
+    }
+}
+
+/// How `define` prints words.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Ansi,
+    Plain,
+    Markdown,
+    Html,
+    Json,
+}
+
+pub struct Colors {
+    pub language: Color,
+    pub part_of_speech: Color,
+    pub link: Color,
+}
+
+/// How to look up and print words. The REPL changes these between lookups.
+pub struct Settings {
+    pub filters: Filters,
+    /// Languages to print first, in this order.
+    pub preferred_languages: Vec<String>,
+    /// Extra names for languages, from the config file.
+    pub language_aliases: HashMap<String, String>,
+    pub raw: bool,
+    pub related: bool,
+    pub thesaurus: bool,
+    pub format: Format,
+    pub width: usize,
+    pub colors: Colors,
+}
+
+impl Settings {
+    pub fn renderer(&self) -> Box<dyn Renderer> {
+        match self.format {
+            Format::Plain => Box::new(render::Plain { width: self.width }),
+            Format::Markdown => Box::new(render::Markdown { url: render::wiktionary_url }),
+            Format::Html => Box::new(render::Html { url: render::wiktionary_url }),
+            Format::Ansi | Format::Json => Box::new(render::Ansi {
+                width: self.width,
+                language: self.colors.language,
+                heading: self.colors.part_of_speech,
+                link: self.colors.link,
+            }),
+        }
+    }
+
+    /// Resolves languages given by the user, which may be comma-separated lists of names, codes
+    /// or aliases.
+    pub fn resolve_languages<S: AsRef<str>>(
+        &self,
+        dict: &Dictionary,
+        languages: &[S],
+    ) -> define3::Result<Vec<String>> {
+        let mut resolved = Vec::new();
+        for language in languages.iter().flat_map(|languages| languages.as_ref().split(',')) {
+            let language = language.trim();
+            if language.is_empty() {
+                continue;
+            }
+            let language = self
+                .language_aliases
+                .iter()
+                .find(|(alias, _)| alias.to_lowercase() == language.to_lowercase())
+                .map_or(language, |(_, target)| target.as_str());
+            let language = dict.resolve_language(language)?;
+            if !resolved.contains(&language) {
+                resolved.push(language);
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Resolves sources given by the user, which may be comma-separated lists of names.
+    pub fn resolve_sources<S: AsRef<str>>(&self, dict: &Dictionary, sources: &[S]) -> define3::Result<Vec<String>> {
+        let mut resolved = Vec::new();
+        for source in sources.iter().flat_map(|sources| sources.as_ref().split(',')) {
+            let source = source.trim();
+            if source.is_empty() {
+                continue;
+            }
+            let source = dict.resolve_source(source)?;
+            if !resolved.contains(&source) {
+                resolved.push(source);
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Resolves parts of speech given by the user, which may be comma-separated lists of headings
+    /// or abbreviations.
+    pub fn resolve_parts_of_speech<S: AsRef<str>>(
+        &self,
+        dict: &Dictionary,
+        parts_of_speech: &[S],
+    ) -> define3::Result<Vec<String>> {
+        let mut resolved = Vec::new();
+        for pos in parts_of_speech.iter().flat_map(|poses| poses.as_ref().split(',')) {
+            let pos = pos.trim();
+            if pos.is_empty() {
+                continue;
+            }
+            for pos in dict.resolve_part_of_speech(pos)? {
+                if !resolved.contains(&pos) {
+                    resolved.push(pos);
+                }
+            }
+        }
+        Ok(resolved)
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "print this help text");
+    opts.optflag("r", "raw", "don't expand wiki templates");
+    opts.optmulti("l", "language", "only print these languages, given by name, code or alias (repeatable or comma-separated)", "LANG");
+    opts.optmulti("", "exclude-lang", "don't print these languages", "LANG");
+    opts.optflag("", "all-languages", "ignore the languages in the config file");
+    opts.optmulti("p", "pos", "only print these parts of speech, like noun or adj (repeatable or comma-separated)", "POS");
+    opts.optmulti("s", "source", "only print definitions from these dictionaries, like enwikt (repeatable or comma-separated)", "SOURCE");
+    opts.optflag("", "related", "also print synonyms, antonyms and other related terms");
+    opts.optflag("", "thesaurus", "only print related terms, including reverse synonyms");
+    opts.optopt("f", "format", "how to print results: ansi (the default), plain, markdown, html or json", "FORMAT");
+    opts.optflag("", "json", "print results as JSON, like --format json");
+    opts.optopt("w", "width", "wrap definitions at N columns (default: the terminal's width)", "N");
+    opts.optopt("", "color", "whether to color output: always, never or auto (the default, which colors terminals unless NO_COLOR is set)", "WHEN");
+    opts.optflag("i", "interactive", "look up words from a prompt (the default without WORD)");
+    opts.optflag("t", "tui", "browse definitions full-screen, following links between words");
+    let brief = format!("Usage: {} [options] [WORD]", args[0]);
+    let matches = match opts.parse(&args[1..]) {
+        Ok(matches) => matches,
+        Err(e) => {
+            eprintln!("{}: {}", args[0], e);
+            eprint!("{}", opts.usage(&brief));
+            process::exit(2);
+        }
+    };
+    if matches.opt_present("h") || matches.free.len() > 1 {
+        print!("{}", opts.usage(&brief));
+        return;
+    }
+
+    if let Err(e) = run(&matches) {
+        eprintln!("{}: {}", args[0], e);
+        process::exit(1);
+    }
+}
+
+fn run(matches: &Matches) -> Result<(), Box<dyn error::Error>> {
+    let expander = Expander::new();
+
+    let dict = Dictionary::open(Dictionary::default_path().ok_or(Error::NoDataDir)?)?;
+
+    let config = Config::load()?;
+
+    let colorize = match matches.opt_str("color").as_deref() {
+        Some("always") => true,
+        Some("never") => false,
+        Some("auto") | None => {
+            env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && io::stdout().is_terminal()
+        }
+        Some(other) => {
+            return Err(format!("--color must be always, never or auto, not \"{}\"", other).into())
+        }
+    };
+    colored::control::set_override(colorize);
+
+    let format = match matches.opt_str("f").as_deref() {
+        _ if matches.opt_present("json") => Format::Json,
+        Some("ansi") | None => Format::Ansi,
+        Some("plain") => Format::Plain,
+        Some("markdown") | Some("md") => Format::Markdown,
+        Some("html") => Format::Html,
+        Some("json") => Format::Json,
+        Some(other) => {
+            return Err(format!("--format must be ansi, plain, markdown, html or json, not \"{}\"", other).into())
+        }
+    };
+
+    let width = match matches.opt_str("w") {
+        Some(width) => width.parse().map_err(|e| format!("bad --width \"{}\": {}", width, e))?,
+        None => config.width.unwrap_or_else(terminal_width),
+    };
+    let mut settings = Settings {
+        filters: Filters::default(),
+        preferred_languages: Vec::new(),
+        language_aliases: config.language_aliases.clone(),
+        raw: matches.opt_present("r") || config.expand_templates == Some(false),
+        related: matches.opt_present("related"),
+        thesaurus: matches.opt_present("thesaurus"),
+        format,
+        width,
+        colors: Colors {
+            language: config::color(&config.colors.language, Color::Green)?,
+            part_of_speech: config::color(&config.colors.part_of_speech, Color::White)?,
+            link: config::color(&config.colors.link, Color::Cyan)?,
+        },
+    };
+    let languages = matches.opt_strs("l");
+    settings.filters.languages = if !languages.is_empty() {
+        settings.resolve_languages(&dict, &languages)?
+    } else if !matches.opt_present("all-languages") {
+        settings.resolve_languages(&dict, &config.languages)?
+    } else {
+        Vec::new()
+    };
+    let mut exclude_languages = config.exclude_languages.clone();
+    exclude_languages.extend(matches.opt_strs("exclude-lang"));
+    settings.filters.exclude_languages = settings.resolve_languages(&dict, &exclude_languages)?;
+    settings.filters.parts_of_speech = settings.resolve_parts_of_speech(&dict, &matches.opt_strs("p"))?;
+    settings.filters.sources = settings.resolve_sources(&dict, &matches.opt_strs("s"))?;
+    settings.preferred_languages = if !languages.is_empty() {
+        settings.filters.languages.clone()
+    } else if !config.preferred_languages.is_empty() {
+        settings.resolve_languages(&dict, &config.preferred_languages)?
+    } else {
+        settings.resolve_languages(&dict, &config.languages)?
+    };
+
+    let word = matches.free.first().map(|word| word.as_str());
+    if matches.opt_present("t") {
+        return tui::run(&dict, &expander, settings, word);
+    }
+    match word {
+        Some(word) if !matches.opt_present("i") => define(&dict, &expander, word, &settings)?,
+        _ => repl::run(&dict, &expander, settings)?,
+    }
+    Ok(())
+}
+
+// The width of the terminal we're printing to, or 80 columns if it isn't one.
+fn terminal_width() -> usize {
+    if !io::stdout().is_terminal() {
+        return 80;
+    }
+    crossterm::terminal::size().map_or(80, |(width, _)| width as usize)
+}
+
+/// Expands the templates in a definition, unless `raw` is set.
+pub fn expand_templates(dict: &Dictionary, expander: &Expander, raw: bool, s: &str) -> String {
+    if raw {
+        s.to_owned()
+    } else {
+        expander.expand(dict, s)
+    }
+}
+
+pub fn define(
+    dict: &Dictionary,
+    expander: &Expander,
+    word: &str,
+    settings: &Settings,
+) -> define3::Result<()> {
+    let filters = &settings.filters;
+    let requested = word;
+    let word = if settings.thesaurus {
+        let word = dict.redirect(word, filters)?.unwrap_or_else(|| word.to_owned());
+        let mut relations = dict.relations(&word, filters)?;
+        relations.extend(dict.reverse_relations(&word, filters)?);
+        Word {
+            name: word,
+            meanings: Vec::new(),
+            relations,
+        }
+    } else {
+        let mut word = dict.lookup(word, filters)?;
+        if !settings.related {
+            word.relations.clear();
+        }
+        word
+    };
+    let expand = |s: &str| expand_templates(dict, expander, settings.raw, s);
+    if settings.format == Format::Json {
+        let json = define3::json::word(&word.name, &word.meanings, &word.relations, |s| {
+            markup::strip_links(&expand(s))
+        });
+        println!("{}", json);
+    } else {
+        let renderer = settings.renderer();
+        if word.name != requested {
+            println!("{} → {}", renderer.text(requested), renderer.link(&word.name, None, &word.name));
+        }
+        let sources = word_sources(&word);
+        if sources.len() > 1 {
+            // a section for each dictionary, in the order they were added
+            for source in sources {
+                println!("{}", renderer.style(renderer.text(source), true, false));
+                let part = Word {
+                    name: word.name.clone(),
+                    meanings: word.meanings.iter().filter(|m| m.source == source).cloned().collect(),
+                    relations: word.relations.iter().filter(|r| r.source == source).cloned().collect(),
+                };
+                let langs = get_defns_by_lang(&part);
+                let relations = get_relations_by_lang(&part.relations);
+                print_words(&langs, &relations, settings, &*renderer, expand);
+            }
+        } else {
+            let langs = get_defns_by_lang(&word);
+            let relations = get_relations_by_lang(&word.relations);
+            print_words(&langs, &relations, settings, &*renderer, expand);
+        }
+    }
+    Ok(())
+}
+
+// The sources `word` has meanings or relations from, in the order they first appear.
+fn word_sources(word: &Word) -> Vec<&str> {
+    let mut sources: Vec<&str> = Vec::new();
+    let all = word.meanings.iter().map(|m| &m.source).chain(word.relations.iter().map(|r| &r.source));
+    for source in all {
+        if !sources.contains(&source.as_str()) {
+            sources.push(source);
+        }
+    }
+    sources
+}
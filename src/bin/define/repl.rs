@@ -0,0 +1,181 @@
+use define3::templates::Expander;
+use define3::Dictionary;
+
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::cell::RefCell;
+
+use {define, Settings};
+
+const COMMANDS: &[&str] = &[":help", ":lang", ":pos", ":quit", ":raw"];
+
+// How many headwords to offer when completing.
+const COMPLETIONS: u32 = 50;
+
+const HELP: &str = "\
+Type a word to define it, or one of these commands:
+  :lang LANGUAGES only show definitions in LANGUAGES (names, codes or aliases,
+                  separated by commas)
+  :lang           show definitions in every language
+  :pos POS        only show definitions of these parts of speech (like noun or
+                  adj, separated by commas)
+  :pos            show every part of speech
+  :raw            toggle expanding wiki templates
+  :help           print this help text
+  :quit           exit (so does Ctrl-D)";
+
+struct DictHelper<'a> {
+    dict: &'a Dictionary,
+    // looked up the first time a language is completed, since it takes a while
+    languages: RefCell<Option<Vec<String>>>,
+}
+
+impl<'a> Completer for DictHelper<'a> {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        if let Some(languages) = line.strip_prefix(":lang ") {
+            // complete the last of a comma-separated list
+            let start = languages.rfind(',').map_or(0, |i| i + 1);
+            let prefix = &languages[start..];
+            let mut languages = self.languages.borrow_mut();
+            if languages.is_none() {
+                *languages = Some(self.dict.languages().unwrap_or_default());
+            }
+            let candidates = languages
+                .iter()
+                .flatten()
+                .filter(|language| language.starts_with(prefix))
+                .cloned()
+                .collect();
+            Ok((":lang ".len() + start, candidates))
+        } else if line.starts_with(':') {
+            let candidates = COMMANDS
+                .iter()
+                .filter(|command| command.starts_with(line))
+                .map(|command| command.to_string())
+                .collect();
+            Ok((0, candidates))
+        } else if line.is_empty() {
+            Ok((0, Vec::new()))
+        } else {
+            Ok((0, self.dict.search(line, COMPLETIONS).unwrap_or_default()))
+        }
+    }
+}
+
+impl<'a> Hinter for DictHelper<'a> {
+    type Hint = String;
+}
+
+impl<'a> Highlighter for DictHelper<'a> {}
+
+impl<'a> Validator for DictHelper<'a> {}
+
+impl<'a> Helper for DictHelper<'a> {}
+
+/// Reads words and commands from a prompt until the user quits, keeping the database open and
+/// the settings from previous commands.
+pub fn run(dict: &Dictionary, expander: &Expander, mut settings: Settings) -> define3::Result<()> {
+    let mut editor: Editor<DictHelper, DefaultHistory> = match Editor::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            eprintln!("Couldn't start the prompt: {}", e);
+            return Ok(());
+        }
+    };
+    editor.set_helper(Some(DictHelper {
+        dict,
+        languages: RefCell::new(None),
+    }));
+    let history_path = dirs::data_dir().map(|mut path| {
+        path.push("define3");
+        path.push("history.txt");
+        path
+    });
+    if let Some(ref path) = history_path {
+        // there's no history the first time around
+        let _ = editor.load_history(path);
+    }
+
+    loop {
+        let line = match editor.readline("define> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                eprintln!("{}", e);
+                break;
+            }
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+
+        let (command, arg) = match line.find(' ') {
+            Some(i) => (&line[..i], line[i + 1..].trim()),
+            None => (line, ""),
+        };
+        match command {
+            ":q" | ":quit" => break,
+            ":h" | ":help" => println!("{}", HELP),
+            ":lang" if arg.is_empty() => {
+                settings.filters.languages.clear();
+                println!("Showing every language");
+            }
+            ":lang" => match settings.resolve_languages(dict, &[arg]) {
+                Ok(languages) => {
+                    println!("Only showing {}", languages.join(", "));
+                    settings.preferred_languages = languages.clone();
+                    settings.filters.languages = languages;
+                }
+                Err(e) => println!("{}", e),
+            },
+            ":pos" if arg.is_empty() => {
+                settings.filters.parts_of_speech.clear();
+                println!("Showing every part of speech");
+            }
+            ":pos" => match settings.resolve_parts_of_speech(dict, &[arg]) {
+                Ok(poses) => {
+                    println!("Only showing {}", poses.join(", "));
+                    settings.filters.parts_of_speech = poses;
+                }
+                Err(e) => println!("{}", e),
+            },
+            ":raw" => {
+                settings.raw = !settings.raw;
+                if settings.raw {
+                    println!("Not expanding templates");
+                } else {
+                    println!("Expanding templates");
+                }
+            }
+            _ if command.starts_with(':') => println!("Unknown command {}; try :help", command),
+            _ => {
+                if let Err(e) = define(dict, expander, line, &settings) {
+                    eprintln!("{}", e);
+                }
+            }
+        }
+    }
+
+    if let Some(ref path) = history_path {
+        if let Err(e) = editor.save_history(path) {
+            eprintln!("Couldn't save history to {}: {}", path.display(), e);
+        }
+    }
+    Ok(())
+}
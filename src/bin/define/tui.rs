@@ -0,0 +1,410 @@
+use define3::markup::{self, Span};
+use define3::templates::Expander;
+use define3::Dictionary;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+use std::error;
+use std::io::{self, Write};
+use textwrap::core::display_width;
+
+use {expand_templates, get_defns_by_lang, language_order, Settings};
+
+const HELP: &str =
+    "←/→ language  ↑/↓ link  Enter follow  Backspace back  f forward  / look up  q quit";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Style {
+    Plain,
+    Heading,
+    Link(usize),
+}
+
+// One screen line, as pieces of text with their styles.
+type Line = Vec<(String, Style)>;
+
+// A part of speech and its definitions.
+type Section = (String, Vec<String>);
+
+// A headword's definitions by language, with templates expanded but links left in.
+struct Page {
+    word: String,
+    langs: Vec<(String, Vec<Section>)>,
+}
+
+struct Browser<'a> {
+    dict: &'a Dictionary,
+    expander: &'a Expander,
+    settings: Settings,
+    page: Page,
+    tab: usize,
+    // the selected link, counting from the top of the current tab
+    link: usize,
+    // whether to scroll to the selected link the next time we draw
+    reveal_link: bool,
+    scroll: usize,
+    back: Vec<(String, usize)>,
+    forward: Vec<(String, usize)>,
+    // what's been typed after pressing /
+    prompt: Option<String>,
+    message: Option<String>,
+}
+
+// Puts the terminal back the way it was, even if we bail out with an error.
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, cursor::Show);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// Word-wraps styled pieces of text to `width` columns.
+fn wrap(pieces: &[(String, Style)], width: usize, indent: usize, hanging: usize) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut line: Line = vec![(" ".repeat(indent), Style::Plain)];
+    let mut line_width = indent;
+    for &(ref text, style) in pieces {
+        for word in text.split_inclusive(' ') {
+            let word_width = display_width(word.trim_end());
+            if line_width + word_width > width && line_width > hanging.max(indent) {
+                lines.push(line);
+                line = vec![(" ".repeat(hanging), Style::Plain)];
+                line_width = hanging;
+            }
+            line_width += display_width(word);
+            match line.last_mut() {
+                Some(&mut (ref mut last, last_style)) if last_style == style => last.push_str(word),
+                _ => line.push((word.to_owned(), style)),
+            }
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+// The terminal color for a color from the settings, or `None` if output isn't colored.
+fn terminal_color(color: colored::Color) -> Option<Color> {
+    use colored::Color as C;
+    if !colored::control::SHOULD_COLORIZE.should_colorize() {
+        return None;
+    }
+    Some(match color {
+        C::Black => Color::Black,
+        C::Red => Color::DarkRed,
+        C::Green => Color::DarkGreen,
+        C::Yellow => Color::DarkYellow,
+        C::Blue => Color::DarkBlue,
+        C::Magenta => Color::DarkMagenta,
+        C::Cyan => Color::DarkCyan,
+        C::White => Color::Grey,
+        C::BrightBlack => Color::DarkGrey,
+        C::BrightRed => Color::Red,
+        C::BrightGreen => Color::Green,
+        C::BrightYellow => Color::Yellow,
+        C::BrightBlue => Color::Blue,
+        C::BrightMagenta => Color::Magenta,
+        C::BrightCyan => Color::Cyan,
+        C::BrightWhite => Color::White,
+    })
+}
+
+impl<'a> Browser<'a> {
+    fn load(&self, word: &str) -> define3::Result<Page> {
+        let found = self.dict.lookup(word, &self.settings.filters)?;
+        let langs = get_defns_by_lang(&found);
+        let mut langs: Vec<(String, Vec<Section>)> = langs
+            .iter()
+            .map(|(lang, poses)| {
+                let poses = poses
+                    .iter()
+                    .map(|(pos, defns)| {
+                        let defns = defns
+                            .iter()
+                            .map(|defn| {
+                                expand_templates(self.dict, self.expander, self.settings.raw, defn)
+                            })
+                            .collect();
+                        (pos.clone(), defns)
+                    })
+                    .collect();
+                (lang.clone(), poses)
+            })
+            .collect();
+        langs.sort_by_key(|(lang, _)| language_order(lang, &self.settings.preferred_languages));
+        Ok(Page {
+            word: found.name,
+            langs,
+        })
+    }
+
+    // Shows `word`, on the tab for `anchor` if it has one.
+    fn navigate(&mut self, word: &str, anchor: Option<&str>) -> define3::Result<()> {
+        let page = self.load(word)?;
+        if page.langs.is_empty() {
+            self.message = Some(format!("No results found for {}.", word));
+            return Ok(());
+        }
+        let previous = std::mem::replace(&mut self.page, page);
+        if !previous.word.is_empty() {
+            self.back.push((previous.word, self.tab));
+        }
+        self.forward.clear();
+        self.tab = anchor
+            .and_then(|anchor| self.page.langs.iter().position(|(lang, _)| lang == anchor))
+            .unwrap_or(0);
+        self.link = 0;
+        self.scroll = 0;
+        self.message = if self.page.word != word {
+            Some(format!("{} → {}", word, self.page.word))
+        } else {
+            None
+        };
+        Ok(())
+    }
+
+    // Goes back (or forward) through the history.
+    fn go(&mut self, back: bool) -> define3::Result<()> {
+        let next = if back { self.back.last() } else { self.forward.last() };
+        if let Some((word, tab)) = next.cloned() {
+            // the history only changes once the word has loaded
+            let page = self.load(&word)?;
+            let current = (std::mem::replace(&mut self.page, page).word, self.tab);
+            let (from, to) = if back {
+                (&mut self.back, &mut self.forward)
+            } else {
+                (&mut self.forward, &mut self.back)
+            };
+            from.pop();
+            to.push(current);
+            self.tab = tab.min(self.page.langs.len().saturating_sub(1));
+            self.link = 0;
+            self.scroll = 0;
+            self.message = None;
+        }
+        Ok(())
+    }
+
+    // The links on the current tab, in order.
+    fn links(&self) -> Vec<(String, Option<String>)> {
+        let mut links = Vec::new();
+        if let Some((_, poses)) = self.page.langs.get(self.tab) {
+            for (_, defns) in poses {
+                for defn in defns {
+                    for span in markup::spans(defn) {
+                        if let Span::Link { target, anchor, .. } = span {
+                            links.push((target.to_owned(), anchor.map(|a| a.to_owned())));
+                        }
+                    }
+                }
+            }
+        }
+        links
+    }
+
+    // The body of the current tab, wrapped to `width`.
+    fn body(&self, width: usize) -> Vec<Line> {
+        let mut lines = Vec::new();
+        let mut link = 0;
+        if let Some((_, poses)) = self.page.langs.get(self.tab) {
+            for (pos, defns) in poses {
+                lines.push(vec![(pos.clone(), Style::Heading)]);
+                for (i, defn) in defns.iter().enumerate() {
+                    let mut pieces = vec![(format!("{}. ", i + 1), Style::Plain)];
+                    for span in markup::spans(defn) {
+                        match span {
+                            Span::Text(text) => pieces.push((text.to_owned(), Style::Plain)),
+                            Span::Link { text, .. } => {
+                                pieces.push((text.to_owned(), Style::Link(link)));
+                                link += 1;
+                            }
+                            Span::Bold | Span::Italic => (),
+                        }
+                    }
+                    lines.extend(wrap(&pieces, width, 2, 5));
+                }
+                lines.push(Vec::new());
+            }
+        }
+        lines
+    }
+
+    fn draw<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (width as usize, height as usize);
+        let body_height = height.saturating_sub(3);
+        let body = self.body(width);
+
+        let selected_line = body.iter().position(|line| {
+            line.iter().any(|&(_, style)| style == Style::Link(self.link))
+        });
+        if let (true, Some(line)) = (self.reveal_link, selected_line) {
+            self.reveal_link = false;
+            if line < self.scroll {
+                self.scroll = line;
+            } else if line >= self.scroll + body_height {
+                self.scroll = line + 1 - body_height;
+            }
+        }
+        self.scroll = self.scroll.min(body.len().saturating_sub(body_height));
+
+        queue!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        queue!(out, SetAttribute(Attribute::Bold), Print(&self.page.word), SetAttribute(Attribute::Reset))?;
+
+        queue!(out, cursor::MoveTo(0, 1))?;
+        let language_color = terminal_color(self.settings.colors.language);
+        let heading_color = terminal_color(self.settings.colors.part_of_speech);
+        let link_color = terminal_color(self.settings.colors.link);
+        for (i, (lang, _)) in self.page.langs.iter().enumerate() {
+            if i == self.tab {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            if let Some(color) = language_color {
+                queue!(out, SetForegroundColor(color))?;
+            }
+            queue!(out, Print(format!(" {} ", lang)))?;
+            queue!(out, ResetColor, SetAttribute(Attribute::Reset), Print(" "))?;
+        }
+
+        for (row, line) in body.iter().skip(self.scroll).take(body_height).enumerate() {
+            queue!(out, cursor::MoveTo(0, row as u16 + 2))?;
+            for &(ref text, style) in line {
+                match style {
+                    Style::Plain => queue!(out, Print(text))?,
+                    Style::Heading => {
+                        if let Some(color) = heading_color {
+                            queue!(out, SetForegroundColor(color))?;
+                        }
+                        queue!(out, SetAttribute(Attribute::Bold), Print(text))?;
+                    }
+                    Style::Link(link) => {
+                        if link == self.link {
+                            queue!(out, SetAttribute(Attribute::Reverse))?;
+                        }
+                        if let Some(color) = link_color {
+                            queue!(out, SetForegroundColor(color))?;
+                        }
+                        queue!(out, SetAttribute(Attribute::Underlined), Print(text))?;
+                    }
+                }
+                queue!(out, ResetColor, SetAttribute(Attribute::Reset))?;
+            }
+        }
+
+        queue!(out, cursor::MoveTo(0, height.saturating_sub(1) as u16))?;
+        match (&self.prompt, &self.message) {
+            (Some(prompt), _) => queue!(out, Print(format!("Look up: {}", prompt)), cursor::Show)?,
+            (None, Some(message)) => queue!(out, Print(message), cursor::Hide)?,
+            (None, None) => queue!(out, SetAttribute(Attribute::Dim), Print(HELP), SetAttribute(Attribute::Reset), cursor::Hide)?,
+        }
+        out.flush()
+    }
+
+    // Handles a key press, returning false when it's time to quit.
+    fn key(&mut self, key: KeyEvent) -> define3::Result<bool> {
+        if let Some(mut prompt) = self.prompt.take() {
+            match key.code {
+                KeyCode::Enter if !prompt.trim().is_empty() => self.navigate(prompt.trim(), None)?,
+                KeyCode::Esc | KeyCode::Enter => (),
+                KeyCode::Backspace => {
+                    prompt.pop();
+                    self.prompt = Some(prompt);
+                }
+                KeyCode::Char(c) => {
+                    prompt.push(c);
+                    self.prompt = Some(prompt);
+                }
+                _ => self.prompt = Some(prompt),
+            }
+            return Ok(true);
+        }
+
+        let tabs = self.page.langs.len().max(1);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(false),
+            KeyCode::Right | KeyCode::Tab | KeyCode::Char('l') => {
+                self.tab = (self.tab + 1) % tabs;
+                self.link = 0;
+                self.scroll = 0;
+            }
+            KeyCode::Left | KeyCode::BackTab | KeyCode::Char('h') => {
+                self.tab = (self.tab + tabs - 1) % tabs;
+                self.link = 0;
+                self.scroll = 0;
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.link + 1 < self.links().len() => {
+                self.link += 1;
+                self.reveal_link = true;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.link = self.link.saturating_sub(1);
+                self.reveal_link = true;
+            }
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll += 10,
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::Enter => {
+                if let Some((target, anchor)) = self.links().into_iter().nth(self.link) {
+                    // links like [[#English]] point into the same page
+                    let target = if target.is_empty() { self.page.word.clone() } else { target };
+                    self.navigate(&target, anchor.as_deref())?;
+                }
+            }
+            KeyCode::Backspace | KeyCode::Char('b') => self.go(true)?,
+            KeyCode::Char('f') => self.go(false)?,
+            KeyCode::Char('/') => {
+                self.message = None;
+                self.prompt = Some(String::new());
+            }
+            _ => (),
+        }
+        Ok(true)
+    }
+}
+
+/// Browses definitions full-screen, starting from `word` if there is one.
+pub fn run(
+    dict: &Dictionary,
+    expander: &Expander,
+    settings: Settings,
+    word: Option<&str>,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut browser = Browser {
+        dict,
+        expander,
+        settings,
+        page: Page {
+            word: String::new(),
+            langs: Vec::new(),
+        },
+        tab: 0,
+        link: 0,
+        reveal_link: false,
+        scroll: 0,
+        back: Vec::new(),
+        forward: Vec::new(),
+        prompt: None,
+        message: None,
+    };
+    match word {
+        Some(word) => browser.navigate(word, None)?,
+        None => browser.prompt = Some(String::new()),
+    }
+
+    let mut out = io::stdout();
+    terminal::enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    execute!(out, EnterAlternateScreen, cursor::Hide)?;
+
+    loop {
+        browser.draw(&mut out)?;
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press && !browser.key(key)? => break,
+            _ => (),
+        }
+    }
+    Ok(())
+}
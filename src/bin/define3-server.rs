@@ -0,0 +1,97 @@
+extern crate define3;
+extern crate getopts;
+extern crate tiny_http;
+
+use define3::templates::Expander;
+use define3::{server, Dictionary, Error};
+
+use getopts::{Matches, Options};
+use std::error;
+use std::path::PathBuf;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use tiny_http::{Header, Response, Server};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let mut opts = Options::new();
+    opts.optflag("h", "help", "print this help text");
+    opts.optopt("a", "addr", "address to listen on (default 127.0.0.1:8080)", "HOST:PORT");
+    opts.optopt("", "db", "database to serve (default: the one build_definitions_db makes)", "PATH");
+    opts.optopt("j", "threads", "how many requests to answer at once (default 4)", "N");
+    let brief = format!("Usage: {} [options]", args[0]);
+    let matches = match opts.parse(&args[1..]) {
+        Ok(matches) => matches,
+        Err(e) => {
+            eprintln!("{}: {}", args[0], e);
+            eprint!("{}", opts.usage(&brief));
+            process::exit(2);
+        }
+    };
+    if matches.opt_present("h") {
+        print!("{}", opts.usage(&brief));
+        return;
+    }
+
+    if let Err(e) = run(&matches) {
+        eprintln!("{}: {}", args[0], e);
+        process::exit(1);
+    }
+}
+
+fn run(matches: &Matches) -> Result<(), Box<dyn error::Error>> {
+    let db_path = match matches.opt_str("db") {
+        Some(path) => PathBuf::from(path),
+        None => Dictionary::default_path().ok_or(Error::NoDataDir)?,
+    };
+    let threads: usize = match matches.opt_str("threads") {
+        Some(threads) => threads.parse()?,
+        None => 4,
+    };
+    let addr = matches.opt_str("addr").unwrap_or_else(|| "127.0.0.1:8080".to_owned());
+
+    // Each worker gets its own read-only connection, since connections can't be shared between
+    // threads. Opening them all up front reports a missing database before we start listening.
+    let dicts = (0..threads.max(1))
+        .map(|_| Dictionary::open(&db_path))
+        .collect::<define3::Result<Vec<Dictionary>>>()?;
+
+    let server = Arc::new(Server::http(&addr).map_err(|e| format!("couldn't listen on {}: {}", addr, e))?);
+    println!("Serving {} on http://{}", db_path.display(), addr);
+
+    let workers: Vec<_> = dicts
+        .into_iter()
+        .map(|dict| {
+            let server = server.clone();
+            thread::spawn(move || serve(&server, &dict))
+        })
+        .collect();
+    for worker in workers {
+        let _ = worker.join();
+    }
+    Ok(())
+}
+
+fn serve(server: &Server, dict: &Dictionary) {
+    let expander = Expander::new();
+    for request in server.incoming_requests() {
+        let accept = request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv("Accept"))
+            .map(|header| header.value.as_str().to_owned());
+        let method = request.method().as_str().to_owned();
+        let response = server::handle(dict, &expander, &method, request.url(), accept.as_deref());
+
+        let mut http_response = Response::from_string(response.body)
+            .with_status_code(response.status)
+            .with_header(Header::from_bytes("Content-Type", response.content_type).unwrap());
+        if let Some(location) = response.location {
+            http_response.add_header(Header::from_bytes("Location", location).unwrap());
+        }
+        if let Err(e) = request.respond(http_response) {
+            eprintln!("Couldn't send a response: {}", e);
+        }
+    }
+}
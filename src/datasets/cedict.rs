@@ -0,0 +1,116 @@
+//! CC-CEDICT (https://cc-cedict.org/), a Chinese-English dictionary with a line per entry:
+//!
+//! ```text
+//! 貓 猫 [mao1] /cat/CL:隻|只[zhi1]/
+//! ```
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::Words;
+use error::{Error, Result};
+use {Meaning, Word};
+
+/// The source CC-CEDICT's words are saved under.
+pub const SOURCE: &str = "cedict";
+
+// CC-CEDICT doesn't give parts of speech.
+const PART_OF_SPEECH: &str = "Unclassified";
+
+/// Reads the CC-CEDICT file at `path`. Each entry is a meaning of its traditional and simplified
+/// headwords, with its glosses after its pinyin.
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<Word>> {
+    let path = path.as_ref();
+    let io_error = |source| Error::Io {
+        path: path.to_owned(),
+        source,
+    };
+    let file = File::open(path).map_err(io_error)?;
+    let mut words = Words::default();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(io_error)?;
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (traditional, simplified, reading, glosses) = parse_line(line).ok_or_else(|| Error::MalformedLine {
+            path: path.to_owned(),
+            line: i + 1,
+        })?;
+        let definition = format!("({}) {}", pinyin(reading), glosses.join("; "));
+        let mut headwords = vec![traditional];
+        if simplified != traditional {
+            headwords.push(simplified);
+        }
+        for headword in headwords {
+            words.get(headword).meanings.push(Meaning {
+                language: "Chinese".to_owned(),
+                part_of_speech: PART_OF_SPEECH.to_owned(),
+                definition: definition.clone(),
+                source: SOURCE.to_owned(),
+            });
+        }
+    }
+    Ok(words.into_vec())
+}
+
+// Splits an entry into its traditional and simplified headwords, its reading and its glosses.
+fn parse_line(line: &str) -> Option<(&str, &str, &str, Vec<&str>)> {
+    let mut parts = line.splitn(3, ' ');
+    let traditional = parts.next()?;
+    let simplified = parts.next()?;
+    let rest = parts.next()?.strip_prefix('[')?;
+    let end = rest.find(']')?;
+    let reading = &rest[..end];
+    let glosses: Vec<&str> = rest[end + 1..]
+        .trim()
+        .strip_prefix('/')?
+        .strip_suffix('/')?
+        .split('/')
+        .filter(|gloss| !gloss.is_empty())
+        .collect();
+    if glosses.is_empty() {
+        return None;
+    }
+    Some((traditional, simplified, reading, glosses))
+}
+
+/// Converts pinyin with tone numbers, as CC-CEDICT writes it, to pinyin with tone marks, e.g.
+/// `lu:4 se4` to `lǜ sè`. Syllables without a tone number are left as they are.
+pub fn pinyin(numbered: &str) -> String {
+    numbered.split(' ').map(syllable).collect::<Vec<_>>().join(" ")
+}
+
+const VOWELS: &str = "aeiouüAEIOUÜ";
+const MARKED: [&str; 12] = [
+    "āáǎà", "ēéěè", "īíǐì", "ōóǒò", "ūúǔù", "ǖǘǚǜ", "ĀÁǍÀ", "ĒÉĚÈ", "ĪÍǏÌ", "ŌÓǑÒ", "ŪÚǓÙ", "ǕǗǙǛ",
+];
+
+fn syllable(numbered: &str) -> String {
+    let tone = match numbered.chars().last().and_then(|c| c.to_digit(10)) {
+        Some(tone @ 1..=5) => tone as usize,
+        _ => return numbered.to_owned(),
+    };
+    let letters = numbered[..numbered.len() - 1].replace("u:", "ü").replace("U:", "Ü");
+    if !letters.chars().all(|c| c.is_ascii_alphabetic() || c == 'ü' || c == 'Ü') {
+        return numbered.to_owned();
+    }
+    let lower = letters.to_lowercase();
+    // the tone goes on a or e, on the o of ou, or else on the last vowel
+    let marked = lower
+        .find('a')
+        .or_else(|| lower.find('e'))
+        .or_else(|| lower.find("ou"))
+        .or_else(|| lower.rfind(|c| VOWELS.contains(c)));
+    let i = match marked {
+        Some(i) if tone < 5 => i,
+        Some(_) => return letters,
+        None => return numbered.to_owned(),
+    };
+    // lowercasing doesn't move these letters
+    let vowel = letters[i..].chars().next().unwrap();
+    let row = VOWELS.chars().position(|c| c == vowel).unwrap();
+    let mark = MARKED[row].chars().nth(tone - 1).unwrap();
+    format!("{}{}{}", &letters[..i], mark, &letters[i + vowel.len_utf8()..])
+}
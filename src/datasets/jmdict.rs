@@ -0,0 +1,187 @@
+//! JMdict (https://www.edrdg.org/jmdict/j_jmdict.html), a Japanese dictionary in XML, with an
+//! entry per word:
+//!
+//! ```xml
+//! <entry>
+//! <ent_seq>1467640</ent_seq>
+//! <k_ele><keb>猫</keb></k_ele>
+//! <r_ele><reb>ねこ</reb></r_ele>
+//! <sense><pos>&n;</pos><gloss>cat</gloss></sense>
+//! </entry>
+//! ```
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use super::Words;
+use error::{Error, Result};
+use {Meaning, Word};
+
+/// The source JMdict's words are saved under.
+pub const SOURCE: &str = "jmdict";
+
+/// Maps a JMdict part of speech, the name of the entity it's written as, to a Wiktionary heading.
+/// Codes that only qualify another part of speech, like `vt` for transitive verbs, map to that.
+pub fn part_of_speech(code: &str) -> Option<&'static str> {
+    let heading = match code {
+        "n-pr" => "Proper noun",
+        "n" | "n-adv" | "n-t" | "n-pref" | "n-suf" => "Noun",
+        "adv" | "adv-to" => "Adverb",
+        "aux" | "aux-v" | "aux-adj" => "Auxiliary",
+        "conj" => "Conjunction",
+        "cop" => "Copula",
+        "ctr" => "Counter",
+        "exp" => "Phrase",
+        "int" => "Interjection",
+        "num" => "Numeral",
+        "pn" => "Pronoun",
+        "pref" => "Prefix",
+        "prt" => "Particle",
+        "suf" => "Suffix",
+        _ if code.starts_with("adj") => "Adjective",
+        _ if code.starts_with('v') => "Verb",
+        _ => return None,
+    };
+    Some(heading)
+}
+
+// What the text being read is part of.
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Headword,
+    PartOfSpeech,
+    Gloss,
+    Other,
+}
+
+// An entry being read.
+#[derive(Default)]
+struct Entry {
+    headwords: Vec<String>,
+    senses: Vec<(Vec<&'static str>, Vec<String>)>,
+    parts_of_speech: Vec<&'static str>,
+    glosses: Vec<String>,
+}
+
+impl Entry {
+    fn end_sense(&mut self) {
+        let glosses = std::mem::take(&mut self.glosses);
+        let parts_of_speech = std::mem::take(&mut self.parts_of_speech);
+        if glosses.is_empty() {
+            return;
+        }
+        // a sense without parts of speech has those of the one before
+        let parts_of_speech = match self.senses.last() {
+            Some((previous, _)) if parts_of_speech.is_empty() => previous.clone(),
+            _ => parts_of_speech,
+        };
+        self.senses.push((parts_of_speech, glosses));
+    }
+
+    // Adds a meaning to each of the entry's headwords for each part of speech of each sense.
+    fn save(self, words: &mut Words) {
+        for headword in &self.headwords {
+            let word = words.get(headword);
+            for (parts_of_speech, glosses) in &self.senses {
+                let definition = glosses.join("; ");
+                let unclassified = ["Unclassified"];
+                let parts_of_speech = if parts_of_speech.is_empty() { &unclassified[..] } else { parts_of_speech };
+                for part_of_speech in parts_of_speech {
+                    word.meanings.push(Meaning {
+                        language: "Japanese".to_owned(),
+                        part_of_speech: (*part_of_speech).to_owned(),
+                        definition: definition.clone(),
+                        source: SOURCE.to_owned(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+// Whether a gloss is in English, which glosses are unless they say otherwise.
+fn is_english(gloss: &BytesStart) -> bool {
+    gloss
+        .attributes()
+        .flatten()
+        .find(|attribute| attribute.key == b"xml:lang")
+        .is_none_or(|attribute| &*attribute.value == b"eng")
+}
+
+fn xml_error<B: BufRead>(reader: &Reader<B>, source: ::quick_xml::Error) -> Error {
+    Error::Xml {
+        position: reader.buffer_position(),
+        title: None,
+        source,
+    }
+}
+
+/// Reads the JMdict file at `path`. Each sense of an entry is a meaning of each of its kanji and
+/// kana headwords, with its English glosses as the definition, under each of its parts of speech.
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<Word>> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|source| Error::Io {
+        path: path.to_owned(),
+        source,
+    })?;
+    let mut reader = Reader::from_reader(BufReader::new(file));
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    let mut words = Words::default();
+    let mut entry = Entry::default();
+    let mut field = Field::Other;
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Event::Start(ref e)) => {
+                field = match e.name() {
+                    b"entry" => {
+                        entry = Entry::default();
+                        Field::Other
+                    }
+                    b"keb" | b"reb" => Field::Headword,
+                    b"pos" => Field::PartOfSpeech,
+                    b"gloss" if is_english(e) => Field::Gloss,
+                    _ => Field::Other,
+                }
+            }
+            // parts of speech are entities declared in the DTD, like &n;
+            Ok(Event::Text(ref e)) if field == Field::PartOfSpeech => {
+                let code = String::from_utf8_lossy(e.escaped());
+                let code = code.trim_start_matches('&').trim_end_matches(';');
+                if let Some(part_of_speech) = part_of_speech(code) {
+                    if !entry.parts_of_speech.contains(&part_of_speech) {
+                        entry.parts_of_speech.push(part_of_speech);
+                    }
+                }
+            }
+            Ok(Event::Text(ref e)) if field != Field::Other => {
+                let text = e.unescape_and_decode(&reader).map_err(|e| xml_error(&reader, e))?;
+                match field {
+                    Field::Headword => {
+                        if !entry.headwords.contains(&text) {
+                            entry.headwords.push(text);
+                        }
+                    }
+                    _ => entry.glosses.push(text),
+                }
+            }
+            Ok(Event::End(ref e)) => {
+                field = Field::Other;
+                match e.name() {
+                    b"sense" => entry.end_sense(),
+                    b"entry" => std::mem::take(&mut entry).save(&mut words),
+                    _ => (),
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(xml_error(&reader, e)),
+            _ => (),
+        }
+        buf.clear();
+    }
+    Ok(words.into_vec())
+}
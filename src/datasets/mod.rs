@@ -0,0 +1,42 @@
+//! Readers for dictionaries other than Wiktionary, which build_definitions_db can add to the
+//! database alongside it. Each reads a whole dataset into words, one per headword, whose meanings
+//! and relations are in the order the dataset has them.
+
+pub mod cedict;
+pub mod jmdict;
+pub mod wordnet;
+
+use std::collections::HashMap;
+
+/// The formats of the datasets `build_definitions_db import` reads, each saved as the source of
+/// the same name.
+pub const FORMATS: &[&str] = &[jmdict::SOURCE, cedict::SOURCE, wordnet::SOURCE];
+
+use Word;
+
+// Words being read from a dataset, which can have a headword in several of its entries.
+#[derive(Default)]
+struct Words {
+    words: Vec<Word>,
+    index: HashMap<String, usize>,
+}
+
+impl Words {
+    // The word named `name`, which is added after the others if it's new.
+    fn get(&mut self, name: &str) -> &mut Word {
+        let words = &mut self.words;
+        let i = *self.index.entry(name.to_owned()).or_insert_with(|| {
+            words.push(Word {
+                name: name.to_owned(),
+                meanings: Vec::new(),
+                relations: Vec::new(),
+            });
+            words.len() - 1
+        });
+        &mut self.words[i]
+    }
+
+    fn into_vec(self) -> Vec<Word> {
+        self.words
+    }
+}
@@ -0,0 +1,185 @@
+//! WordNet (https://wordnet.princeton.edu/), an English dictionary of sets of synonyms, read from
+//! the `data.noun`, `data.verb`, `data.adj` and `data.adv` files of its database. Each line of
+//! those is a synset: its words, its pointers to other synsets and its gloss.
+//!
+//! ```text
+//! 02121620 05 n 03 cat 0 true_cat 0 kitty 0 002 @ 02120997 n 0000 ~ 02122298 n 0000 | feline mammal usually having thick soft fur
+//! ```
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::Words;
+use error::{Error, Result};
+use {Meaning, Relation, Word};
+
+/// The source WordNet's words are saved under.
+pub const SOURCE: &str = "wordnet";
+
+// The data files, with the letter pointers to their synsets use and their part of speech.
+const FILES: &[(&str, char, &str)] = &[
+    ("data.noun", 'n', "Noun"),
+    ("data.verb", 'v', "Verb"),
+    ("data.adj", 'a', "Adjective"),
+    ("data.adv", 'r', "Adverb"),
+];
+
+/// Maps a WordNet pointer symbol to a relation as stored in the database, for the pointers that
+/// have one.
+pub fn relation(symbol: &str) -> Option<&'static str> {
+    let relation = match symbol {
+        "!" => "antonym",
+        "@" | "@i" => "hypernym",
+        "~" | "~i" => "hyponym",
+        "#m" | "#s" | "#p" => "holonym",
+        "%m" | "%s" | "%p" => "meronym",
+        "+" => "related",
+        _ => return None,
+    };
+    Some(relation)
+}
+
+// A pointer from a synset, or from one of its words, to another synset or one of its words.
+// Words are numbered from 1, and 0 is the whole synset.
+struct Pointer {
+    relation: &'static str,
+    synset: (char, u64),
+    source: usize,
+    target: usize,
+}
+
+struct Synset {
+    part_of_speech: &'static str,
+    words: Vec<String>,
+    pointers: Vec<Pointer>,
+    gloss: String,
+}
+
+// Reads a synset and its offset, which pointers to it give, from a line of a data file.
+fn parse_line(line: &str, part_of_speech: &'static str) -> Option<(u64, Synset)> {
+    let (fields, gloss) = match line.find(" | ") {
+        Some(i) => (&line[..i], line[i + 3..].trim()),
+        None => (line, ""),
+    };
+    let mut fields = fields.split_whitespace();
+    let offset = fields.next()?.parse().ok()?;
+    // the lexicographer file and the synset type
+    fields.nth(1)?;
+    let word_count = usize::from_str_radix(fields.next()?, 16).ok()?;
+    let mut words = Vec::with_capacity(word_count);
+    for _ in 0..word_count {
+        let word = fields.next()?;
+        // adjectives can say where they go, like "galore(ip)"
+        let word = match word.find('(') {
+            Some(i) if word.ends_with(')') => &word[..i],
+            _ => word,
+        };
+        words.push(word.replace('_', " "));
+        fields.next()?;
+    }
+    let pointer_count: usize = fields.next()?.parse().ok()?;
+    let mut pointers = Vec::new();
+    for _ in 0..pointer_count {
+        let symbol = fields.next()?;
+        let target_offset = fields.next()?.parse().ok()?;
+        // satellite adjectives are in data.adj with the rest
+        let target_pos = match fields.next()? {
+            "s" => 'a',
+            pos => pos.chars().next()?,
+        };
+        let words = fields.next()?;
+        if words.len() != 4 || !words.is_ascii() {
+            return None;
+        }
+        let source = usize::from_str_radix(&words[..2], 16).ok()?;
+        let target = usize::from_str_radix(&words[2..], 16).ok()?;
+        if let Some(relation) = relation(symbol) {
+            pointers.push(Pointer {
+                relation,
+                synset: (target_pos, target_offset),
+                source,
+                target,
+            });
+        }
+    }
+    Some((
+        offset,
+        Synset {
+            part_of_speech,
+            words,
+            pointers,
+            gloss: gloss.to_owned(),
+        },
+    ))
+}
+
+/// Reads the WordNet database in the directory `dir`. Each synset is a meaning of each of its
+/// words, with its gloss as the definition. The synset's other words are their synonyms, and its
+/// pointers their other relations, with the part of the gloss before any examples as the sense.
+pub fn read<P: AsRef<Path>>(dir: P) -> Result<Vec<Word>> {
+    let mut synsets: Vec<Synset> = Vec::new();
+    let mut offsets: HashMap<(char, u64), usize> = HashMap::new();
+    for &(name, pos, part_of_speech) in FILES {
+        let path = dir.as_ref().join(name);
+        let io_error = |source| Error::Io {
+            path: path.clone(),
+            source,
+        };
+        let file = File::open(&path).map_err(io_error)?;
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(io_error)?;
+            // the license comes first, indented
+            if line.trim().is_empty() || line.starts_with(' ') {
+                continue;
+            }
+            let (offset, synset) = parse_line(&line, part_of_speech).ok_or_else(|| Error::MalformedLine {
+                path: path.clone(),
+                line: i + 1,
+            })?;
+            offsets.insert((pos, offset), synsets.len());
+            synsets.push(synset);
+        }
+    }
+
+    let mut words = Words::default();
+    for synset in &synsets {
+        let sense = synset.gloss.split(';').next().unwrap_or_default().trim();
+        let relation = |relation: &str, target: &str| Relation {
+            language: "English".to_owned(),
+            relation: relation.to_owned(),
+            target: target.to_owned(),
+            sense: Some(sense.to_owned()),
+            source: SOURCE.to_owned(),
+        };
+        for (i, name) in synset.words.iter().enumerate() {
+            let word = words.get(name);
+            word.meanings.push(Meaning {
+                language: "English".to_owned(),
+                part_of_speech: synset.part_of_speech.to_owned(),
+                definition: synset.gloss.clone(),
+                source: SOURCE.to_owned(),
+            });
+            for synonym in &synset.words {
+                if synonym != name {
+                    word.relations.push(relation("synonym", synonym));
+                }
+            }
+            for pointer in &synset.pointers {
+                if pointer.source != 0 && pointer.source != i + 1 {
+                    continue;
+                }
+                let target = offsets
+                    .get(&pointer.synset)
+                    .and_then(|&target| synsets[target].words.get(pointer.target.max(1) - 1));
+                // nouns and verbs spelled the same are related to each other
+                match target {
+                    Some(target) if target != name => word.relations.push(relation(pointer.relation, target)),
+                    _ => (),
+                }
+            }
+        }
+    }
+    Ok(words.into_vec())
+}
@@ -0,0 +1,712 @@
+//! Lookups against a database built by build_definitions_db.
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection, OpenFlags, OptionalExtension, Row};
+
+use error::{Error, Result, SqlStep};
+use wiktextract::pos_code;
+use {Link, Meaning, Relation, Word};
+
+/// Restricts lookups to some languages, parts of speech or sources. Empty lists don't restrict
+/// anything.
+#[derive(Debug, Default, Clone)]
+pub struct Filters {
+    pub languages: Vec<String>,
+    pub exclude_languages: Vec<String>,
+    pub parts_of_speech: Vec<String>,
+    pub sources: Vec<String>,
+}
+
+impl Filters {
+    // Appends the conditions for these filters to `sql` and their parameters to `params`.
+    fn apply(&self, sql: &mut String, params: &mut Vec<String>) {
+        for (column, operator, values) in &[
+            ("languages.canonical_name", "IN", &self.languages),
+            ("languages.canonical_name", "NOT IN", &self.exclude_languages),
+            ("pos.name", "IN", &self.parts_of_speech),
+            ("sources.name", "IN", &self.sources),
+        ] {
+            if values.is_empty() {
+                continue;
+            }
+            let placeholders: Vec<String> = values
+                .iter()
+                .map(|value| {
+                    params.push(value.clone());
+                    format!("?{}", params.len())
+                })
+                .collect();
+            sql.push_str(&format!(" AND {} {} ({})", column, operator, placeholders.join(", ")));
+        }
+    }
+}
+
+/// The version of the database layout that build_definitions_db writes and `Dictionary` reads.
+/// It goes up whenever older databases can't be read any more.
+pub const SCHEMA_VERSION: u32 = 4;
+
+/// Reads the schema version from the `meta` table. Databases from before it was added are
+/// version 1.
+pub fn schema_version(conn: &Connection) -> Result<u32> {
+    let has_meta = conn
+        .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'meta'")?
+        .exists([])?;
+    if !has_meta {
+        return Ok(1);
+    }
+    let version: Option<String> = conn
+        .query_row("SELECT value FROM meta WHERE key = 'schema_version'", [], |row| row.get(0))
+        .optional()?;
+    Ok(version.and_then(|version| version.parse().ok()).unwrap_or(1))
+}
+
+/// Replaces every table with an empty one, recording the schema version. Entries, redirects,
+/// templates and modules record the source, page and revision they came from, so that updates can
+/// replace them.
+pub fn create_tables(conn: &Connection) -> Result<()> {
+    // children first, so dropping doesn't trip over foreign keys; `words` is from schema version 1
+    conn.execute_batch(
+        "DROP TABLE IF EXISTS senses;
+         DROP TABLE IF EXISTS relations;
+         DROP TABLE IF EXISTS links;
+         DROP TABLE IF EXISTS entries;
+         DROP TABLE IF EXISTS redirects;
+         DROP TABLE IF EXISTS language_aliases;
+         DROP TABLE IF EXISTS languages;
+         DROP TABLE IF EXISTS pos;
+         DROP TABLE IF EXISTS templates;
+         DROP TABLE IF EXISTS modules;
+         DROP TABLE IF EXISTS sources;
+         DROP TABLE IF EXISTS words;
+         DROP TABLE IF EXISTS meta;",
+    ).step("dropping old tables")?;
+
+    conn.execute(
+        "CREATE TABLE meta (
+             key            text primary key,
+             value          text
+         )",
+        [],
+    ).step("creating the meta table")?;
+    conn.execute(
+        "INSERT INTO meta (key, value) VALUES ('schema_version', ?1)",
+        [SCHEMA_VERSION.to_string()],
+    ).step("saving the schema version")?;
+
+    conn.execute(
+        "CREATE TABLE sources (
+             id             integer primary key,
+             name           text not null unique,
+             edition        text,
+             dump_date      text,
+             build_options  text
+         )",
+        [],
+    ).step("creating the sources table")?;
+
+    conn.execute(
+        "CREATE TABLE templates (
+             source_id      integer not null references sources(id),
+             name           text not null,
+             content        text not null,
+             page_id        integer,
+             revision_id    integer
+         )",
+        [],
+    ).step("creating the templates table")?;
+
+    conn.execute(
+        "CREATE TABLE modules (
+             source_id      integer not null references sources(id),
+             name           text not null,
+             content        text not null,
+             page_id        integer,
+             revision_id    integer
+         )",
+        [],
+    ).step("creating the modules table")?;
+
+    conn.execute(
+        "CREATE TABLE languages (
+             id             integer primary key,
+             canonical_name text not null unique,
+             code           text,
+             family         text,
+             scripts        text
+         )",
+        [],
+    ).step("creating the languages table")?;
+
+    conn.execute(
+        "CREATE TABLE language_aliases (
+             alias          text not null,
+             language_id    integer not null references languages(id)
+         )",
+        [],
+    ).step("creating the language_aliases table")?;
+
+    conn.execute(
+        "CREATE TABLE pos (
+             id             integer primary key,
+             name           text not null unique
+         )",
+        [],
+    ).step("creating the pos table")?;
+
+    conn.execute(
+        "CREATE TABLE entries (
+             id             integer primary key,
+             source_id      integer not null references sources(id),
+             name           text not null,
+             page_id        integer,
+             revision_id    integer,
+             unique (source_id, name)
+         )",
+        [],
+    ).step("creating the entries table")?;
+
+    conn.execute(
+        "CREATE TABLE redirects (
+             source_id      integer not null references sources(id),
+             title          text not null,
+             target         text not null,
+             page_id        integer,
+             revision_id    integer,
+             primary key (source_id, title)
+         )",
+        [],
+    ).step("creating the redirects table")?;
+
+    conn.execute(
+        "CREATE TABLE senses (
+             id             integer primary key,
+             entry_id       integer not null references entries(id) on delete cascade,
+             language_id    integer not null references languages(id),
+             pos_id         integer not null references pos(id),
+             definition     text not null
+         )",
+        [],
+    ).step("creating the senses table")?;
+
+    conn.execute(
+        "CREATE TABLE relations (
+             entry_id       integer not null references entries(id) on delete cascade,
+             language_id    integer not null references languages(id),
+             relation       text not null,
+             target         text not null,
+             sense          text
+         )",
+        [],
+    ).step("creating the relations table")?;
+
+    conn.execute(
+        "CREATE TABLE links (
+             entry_id       integer not null references entries(id) on delete cascade,
+             language_id    integer not null references languages(id),
+             target         text not null,
+             anchor         text,
+             text           text not null
+         )",
+        [],
+    ).step("creating the links table")?;
+
+    conn.execute_batch(
+        "create index languages_code_idx on languages(code COLLATE NOCASE);
+         create index languages_canonical_name_idx on languages(canonical_name COLLATE NOCASE);
+         create index language_aliases_alias_idx on language_aliases(alias COLLATE NOCASE);",
+    ).step("creating language indexes")?;
+    // pages are looked up by id while building, and there aren't many templates and modules
+    conn.execute_batch(
+        "create index templates_page_id_idx on templates(page_id);
+         create index modules_page_id_idx on modules(page_id);",
+    ).step("creating page indexes")?;
+    Ok(())
+}
+
+/// Indexes the tables once they're full, which is faster than keeping the indexes up to date. A
+/// database being appended to already has them.
+pub fn create_indexes(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "create index if not exists senses_entry_id_idx on senses(entry_id);
+         create index if not exists senses_language_id_idx on senses(language_id);
+         create index if not exists senses_pos_id_idx on senses(pos_id);
+         create index if not exists relations_entry_id_idx on relations(entry_id);
+         create index if not exists relations_target_idx on relations(target);
+         create index if not exists links_entry_id_idx on links(entry_id);
+         create index if not exists links_target_idx on links(target);",
+    ).step("creating indexes")?;
+    conn.execute_batch(
+        "create index if not exists entries_name_idx on entries(name);
+         create index if not exists entries_page_id_idx on entries(page_id);
+         create index if not exists redirects_page_id_idx on redirects(page_id);",
+    ).step("creating indexes")?;
+    Ok(())
+}
+
+/// Sets up a connection to a database being built to write quickly. Writes go to a write-ahead
+/// log that only waits for the disk when it's copied into the database, so a build that crashes
+/// loses what it saved since its last commit but can be resumed from there.
+pub fn tune_for_building(conn: &Connection) -> Result<()> {
+    conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))
+        .step("tuning the database for building")?;
+    conn.execute_batch(
+        "PRAGMA synchronous = NORMAL;
+         PRAGMA cache_size = -65536;",
+    ).step("tuning the database for building")
+}
+
+// Rows a batch inserts this many at a time.
+const BATCH_ROWS: usize = 100;
+
+/// Rows waiting to be inserted into a table, which go in many to a statement. `flush` has to be
+/// called before the rows are needed, and before committing.
+pub struct Batch {
+    table: &'static str,
+    columns: &'static [&'static str],
+    values: Vec<Value>,
+    step: &'static str,
+}
+
+impl Batch {
+    /// A batch of rows for `table`, with a value for each of `columns`. `step` describes the
+    /// inserts in errors.
+    pub fn new(table: &'static str, columns: &'static [&'static str], step: &'static str) -> Batch {
+        Batch {
+            table,
+            columns,
+            values: Vec::new(),
+            step,
+        }
+    }
+
+    /// Adds a row, inserting the batch if it's full.
+    pub fn push(&mut self, conn: &Connection, row: Vec<Value>) -> Result<()> {
+        self.values.extend(row);
+        if self.values.len() >= BATCH_ROWS * self.columns.len() {
+            self.flush(conn)?;
+        }
+        Ok(())
+    }
+
+    /// Inserts the rows waiting in the batch.
+    pub fn flush(&mut self, conn: &Connection) -> Result<()> {
+        if self.values.is_empty() {
+            return Ok(());
+        }
+        let rows = self.values.len() / self.columns.len();
+        let row = format!("({})", vec!["?"; self.columns.len()].join(", "));
+        let sql = format!(
+            "insert into {} ({}) values {}",
+            self.table,
+            self.columns.join(", "),
+            vec![row; rows].join(", ")
+        );
+        conn.prepare_cached(&sql)
+            .and_then(|mut stmt| stmt.execute(params_from_iter(&self.values)))
+            .step(self.step)?;
+        self.values.clear();
+        Ok(())
+    }
+}
+
+// Definitions, with the word, source, language and part of speech each belongs to.
+const SENSES: &str = "senses
+     JOIN entries ON entries.id = senses.entry_id
+     JOIN sources ON sources.id = entries.source_id
+     JOIN languages ON languages.id = senses.language_id
+     JOIN pos ON pos.id = senses.pos_id";
+
+// Relations, with the word, source and language each belongs to.
+const RELATIONS: &str = "relations
+     JOIN entries ON entries.id = relations.entry_id
+     JOIN sources ON sources.id = entries.source_id
+     JOIN languages ON languages.id = relations.language_id";
+
+// Links, with the word, source and language each belongs to.
+const LINKS: &str = "links
+     JOIN entries ON entries.id = links.entry_id
+     JOIN sources ON sources.id = entries.source_id
+     JOIN languages ON languages.id = links.language_id";
+
+pub struct Dictionary {
+    conn: Connection,
+}
+
+fn meaning_of_row(row: &Row) -> rusqlite::Result<Meaning> {
+    Ok(Meaning {
+        language: row.get(0)?,
+        part_of_speech: row.get(1)?,
+        definition: row.get(2)?,
+        source: row.get(3)?,
+    })
+}
+
+fn relation_of_row(row: &Row) -> rusqlite::Result<Relation> {
+    Ok(Relation {
+        language: row.get(0)?,
+        relation: row.get(1)?,
+        target: row.get(2)?,
+        sense: row.get(3)?,
+        source: row.get(4)?,
+    })
+}
+
+impl Dictionary {
+    /// Where build_definitions_db saves the database by default.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|mut path| {
+            path.push("define3");
+            path.push("define3.sqlite3");
+            path
+        })
+    }
+
+    /// Opens the database at `path` read-only, refusing databases with another schema version.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Dictionary> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(Error::MissingDatabase(path.to_owned()));
+        }
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let version = schema_version(&conn)?;
+        if version != SCHEMA_VERSION {
+            return Err(Error::IncompatibleDatabase {
+                path: path.to_owned(),
+                version,
+                expected: SCHEMA_VERSION,
+            });
+        }
+        Ok(Dictionary { conn })
+    }
+
+    /// Returns a value build_definitions_db recorded about the database, like `schema_version`.
+    pub fn meta(&self, key: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| row.get(0))
+            .optional()?)
+    }
+
+    /// Finds the page `title` redirects to in the first of the filtered sources that has it, if
+    /// it's a redirect. It isn't if any of those sources has an entry for `title` itself, so that
+    /// one source's redirect doesn't hide another's entry.
+    pub fn redirect(&self, title: &str, filters: &Filters) -> Result<Option<String>> {
+        let filters = Filters {
+            sources: filters.sources.clone(),
+            ..Filters::default()
+        };
+        let mut params = vec![title.to_owned()];
+        let mut sql = "SELECT 1 FROM entries JOIN sources ON sources.id = entries.source_id
+                       WHERE entries.name = ?1"
+            .to_owned();
+        filters.apply(&mut sql, &mut params);
+        if self.conn.prepare(&sql)?.exists(params_from_iter(&params))? {
+            return Ok(None);
+        }
+        let mut params = vec![title.to_owned()];
+        let mut sql = "SELECT target FROM redirects JOIN sources ON sources.id = redirects.source_id
+                       WHERE title = ?1"
+            .to_owned();
+        filters.apply(&mut sql, &mut params);
+        sql.push_str(" ORDER BY sources.id LIMIT 1");
+        Ok(self
+            .conn
+            .query_row(&sql, params_from_iter(&params), |row| row.get(0))
+            .optional()?)
+    }
+
+    /// Lists the dictionaries the database has definitions from, like `enwikt`, in the order
+    /// they were added.
+    pub fn sources(&self) -> Result<Vec<String>> {
+        self.distinct("SELECT name FROM sources ORDER BY id")
+    }
+
+    /// Finds the source named `source`, in any case.
+    pub fn resolve_source(&self, source: &str) -> Result<String> {
+        self.conn
+            .query_row("SELECT name FROM sources WHERE name = ?1 COLLATE NOCASE", [source], |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| Error::UnknownSource(source.to_owned()))
+    }
+
+    /// Finds the meanings of `word` and its relations to other words, in the order they appear on
+    /// its Wiktionary page, source by source. If `word` is a redirect, the word it redirects to is
+    /// looked up instead, and the result is named after it.
+    pub fn lookup(&self, word: &str, filters: &Filters) -> Result<Word> {
+        let target = self.redirect(word, filters)?;
+        let word = target.as_deref().unwrap_or(word);
+        let mut params = vec![word.to_owned()];
+        let mut sql = format!(
+            "SELECT languages.canonical_name, pos.name, definition, sources.name FROM {}
+             WHERE entries.name = ?1",
+            SENSES
+        );
+        filters.apply(&mut sql, &mut params);
+        sql.push_str(" ORDER BY sources.id, senses.id");
+        let mut stmt = self.conn.prepare(&sql)?;
+        let meanings = stmt
+            .query_map(params_from_iter(&params), meaning_of_row)?
+            .collect::<rusqlite::Result<Vec<Meaning>>>()?;
+
+        let relations = self.relations(word, filters)?;
+
+        Ok(Word {
+            name: word.to_owned(),
+            meanings,
+            relations,
+        })
+    }
+
+    /// Finds the relations from `word` to other words. Only language filters apply, since
+    /// relations aren't tied to a part of speech.
+    pub fn relations(&self, word: &str, filters: &Filters) -> Result<Vec<Relation>> {
+        let filters = Filters {
+            parts_of_speech: Vec::new(),
+            ..filters.clone()
+        };
+        let mut params = vec![word.to_owned()];
+        let mut sql = format!(
+            "SELECT languages.canonical_name, relation, target, sense, sources.name FROM {}
+             WHERE entries.name = ?1",
+            RELATIONS
+        );
+        filters.apply(&mut sql, &mut params);
+        sql.push_str(" ORDER BY sources.id, relations.rowid");
+        let mut stmt = self.conn.prepare(&sql)?;
+        let relations = stmt
+            .query_map(params_from_iter(&params), relation_of_row)?
+            .collect::<rusqlite::Result<Vec<Relation>>>()?;
+        Ok(relations)
+    }
+
+    /// Finds the words that list `word` as a synonym or antonym, as relations from `word`, since
+    /// those go both ways.
+    pub fn reverse_relations(&self, word: &str, filters: &Filters) -> Result<Vec<Relation>> {
+        let filters = Filters {
+            parts_of_speech: Vec::new(),
+            ..filters.clone()
+        };
+        let mut params = vec![word.to_owned()];
+        let mut sql = format!(
+            "SELECT languages.canonical_name, relation, entries.name, NULL, sources.name FROM {}
+             WHERE target = ?1 AND relation IN ('synonym', 'antonym')",
+            RELATIONS
+        );
+        filters.apply(&mut sql, &mut params);
+        sql.push_str(" ORDER BY relations.rowid");
+        let mut stmt = self.conn.prepare(&sql)?;
+        let relations = stmt
+            .query_map(params_from_iter(&params), relation_of_row)?
+            .collect::<rusqlite::Result<Vec<Relation>>>()?;
+        Ok(relations)
+    }
+
+    /// Finds the links from the definitions of `word` to other pages.
+    pub fn links(&self, word: &str, filters: &Filters) -> Result<Vec<Link>> {
+        let filters = Filters {
+            parts_of_speech: Vec::new(),
+            ..filters.clone()
+        };
+        let mut params = vec![word.to_owned()];
+        let mut sql = format!(
+            "SELECT languages.canonical_name, target, anchor, text FROM {} WHERE entries.name = ?1",
+            LINKS
+        );
+        filters.apply(&mut sql, &mut params);
+        sql.push_str(" ORDER BY links.rowid");
+        let mut stmt = self.conn.prepare(&sql)?;
+        let links = stmt
+            .query_map(params_from_iter(&params), |row| {
+                Ok(Link {
+                    language: row.get(0)?,
+                    target: row.get(1)?,
+                    anchor: row.get(2)?,
+                    text: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<Link>>>()?;
+        Ok(links)
+    }
+
+    /// Finds the words whose definitions link to `target`, alphabetically.
+    pub fn backlinks(&self, target: &str, filters: &Filters) -> Result<Vec<String>> {
+        let filters = Filters {
+            parts_of_speech: Vec::new(),
+            ..filters.clone()
+        };
+        let mut params = vec![target.to_owned()];
+        let mut sql = format!("SELECT DISTINCT entries.name FROM {} WHERE target = ?1", LINKS);
+        filters.apply(&mut sql, &mut params);
+        sql.push_str(" ORDER BY entries.name");
+        let mut stmt = self.conn.prepare(&sql)?;
+        let words = stmt
+            .query_map(params_from_iter(&params), |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(words)
+    }
+
+    /// Finds up to `limit` headwords matching `pattern`, which may use `*` and `?` wildcards.
+    /// Without wildcards, finds headwords starting with `pattern`.
+    pub fn search(&self, pattern: &str, limit: u32) -> Result<Vec<String>> {
+        let glob = if pattern.contains('*') || pattern.contains('?') {
+            pattern.to_owned()
+        } else {
+            // escape glob metacharacters so they match literally
+            let escaped: String = pattern
+                .chars()
+                .map(|c| match c {
+                    '[' => "[[]".to_owned(),
+                    c => c.to_string(),
+                })
+                .collect();
+            format!("{}*", escaped)
+        };
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT name FROM entries
+             WHERE name GLOB ?1 AND EXISTS (SELECT 1 FROM senses WHERE entry_id = entries.id)
+             ORDER BY name LIMIT ?2",
+        )?;
+        let names = stmt
+            .query_map(rusqlite::params![glob, limit], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(names)
+    }
+
+    /// Picks a headword at random, or returns `None` if the database has no words.
+    pub fn random_word(&self) -> Result<Option<String>> {
+        // only words with definitions, which pages of just relations or links aren't
+        let mut stmt = self.conn.prepare(
+            "WITH words AS (
+                 SELECT name FROM entries WHERE EXISTS (SELECT 1 FROM senses WHERE entry_id = entries.id)
+             )
+             SELECT name FROM words
+             LIMIT 1 OFFSET abs(random()) % max((SELECT count(*) FROM words), 1)",
+        )?;
+        let mut rows = stmt.query([])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Finds the heading Wiktionary uses for `language`, which may be a language code like `gsw`,
+    /// another name for it, or its name in any case.
+    pub fn resolve_language(&self, language: &str) -> Result<String> {
+        for sql in &[
+            "SELECT canonical_name FROM languages WHERE canonical_name = ?1 COLLATE NOCASE",
+            "SELECT canonical_name FROM languages WHERE code = ?1 COLLATE NOCASE",
+            "SELECT canonical_name FROM language_aliases
+             JOIN languages ON languages.id = language_aliases.language_id
+             WHERE alias = ?1 COLLATE NOCASE",
+        ] {
+            let mut stmt = self.conn.prepare(sql)?;
+            let mut rows = stmt.query([language])?;
+            if let Some(row) = rows.next()? {
+                return Ok(row.get(0)?);
+            }
+        }
+        Err(Error::UnknownLanguage(language.to_owned()))
+    }
+
+    /// Finds the part of speech headings meant by `part_of_speech`, which may be a heading in any
+    /// case, an abbreviation like `n` or `adj`, or a wiktextract code like `name`. Codes like
+    /// `abbrev` can stand for several headings.
+    pub fn resolve_part_of_speech(&self, part_of_speech: &str) -> Result<Vec<String>> {
+        let wanted = part_of_speech.to_lowercase();
+        let wanted = match wanted.as_str() {
+            "n" => "noun",
+            "v" | "vb" => "verb",
+            "a" => "adj",
+            "pn" | "propn" | "proper" => "name",
+            "interj" => "intj",
+            "abbr" => "abbrev",
+            "char" => "character",
+            other => other,
+        };
+        let all = self.parts_of_speech()?;
+        let exact: Vec<String> = all.iter().filter(|pos| pos.to_lowercase() == wanted).cloned().collect();
+        if !exact.is_empty() {
+            return Ok(exact);
+        }
+        let by_code: Vec<String> = all.into_iter().filter(|pos| pos_code(pos) == wanted).collect();
+        if by_code.is_empty() {
+            return Err(Error::UnknownPartOfSpeech(part_of_speech.to_owned()));
+        }
+        Ok(by_code)
+    }
+
+    /// Lists every language that has a definition, alphabetically.
+    pub fn languages(&self) -> Result<Vec<String>> {
+        self.distinct(
+            "SELECT canonical_name FROM languages
+             WHERE id IN (SELECT language_id FROM senses) ORDER BY canonical_name",
+        )
+    }
+
+    /// Lists every part of speech that has a definition, alphabetically.
+    pub fn parts_of_speech(&self) -> Result<Vec<String>> {
+        self.distinct("SELECT name FROM pos WHERE id IN (SELECT pos_id FROM senses) ORDER BY name")
+    }
+
+    fn distinct(&self, sql: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let values = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(values)
+    }
+
+    /// Returns the wikitext of `Template:name`, from the first dump that had it.
+    pub fn template(&self, name: &str) -> Result<Option<String>> {
+        let mut stmt = self.conn.prepare("SELECT content FROM templates WHERE name = ?1 ORDER BY source_id")?;
+        let mut rows = stmt.query([name])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Calls `f` with every word in the database, in alphabetical order.
+    pub fn for_each_word<F>(&self, mut f: F) -> Result<()>
+    where
+        F: FnMut(Word),
+    {
+        let mut words_stmt = self.conn.prepare(&format!(
+            "SELECT entries.name, languages.canonical_name, pos.name, definition, sources.name FROM {}
+             ORDER BY entries.name, sources.id, senses.id",
+            SENSES
+        ))?;
+        let mut rows = words_stmt.query([])?;
+        let mut current: Option<Word> = None;
+        while let Some(row) = rows.next()? {
+            let name: String = row.get(0)?;
+            let meaning = Meaning {
+                language: row.get(1)?,
+                part_of_speech: row.get(2)?,
+                definition: row.get(3)?,
+                source: row.get(4)?,
+            };
+            match current {
+                Some(ref mut word) if word.name == name => word.meanings.push(meaning),
+                _ => {
+                    if let Some(mut word) = current.take() {
+                        word.relations = self.relations(&word.name, &Filters::default())?;
+                        f(word);
+                    }
+                    current = Some(Word {
+                        name,
+                        meanings: vec![meaning],
+                        relations: Vec::new(),
+                    });
+                }
+            }
+        }
+        if let Some(mut word) = current {
+            word.relations = self.relations(&word.name, &Filters::default())?;
+            f(word);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,344 @@
+//! How each Wiktionary edition marks up its entries. English Wiktionary heads sections with plain
+//! names:
+//!
+//! ```text
+//! ==English==
+//! ===Noun===
+//! # A small domesticated feline animal.
+//! ```
+//!
+//! while French Wiktionary uses templates (`== {{langue|fr}} ==`, `=== {{S|nom|fr}} ===`) and
+//! German Wiktionary lists definitions after `{{Bedeutungen}}`. Languages and parts of speech are
+//! saved under their English names whatever the edition, so that `define -l French -p noun` works
+//! the same on every database.
+
+use parse_wikitext::templates;
+
+/// Names saved in the database, each with the labels an edition uses for it. A name is also a
+/// label for itself.
+pub type Names = &'static [(&'static str, &'static [&'static str])];
+
+/// Where an edition names a language, part of speech or relation.
+#[derive(Debug)]
+pub enum Marker {
+    /// A heading that's just the label, like `===Noun===`.
+    Heading,
+    /// A heading with a template in it, labelled by one of the template's arguments, like `fr` in
+    /// `== {{langue|fr}} ==`. Argument 0 is the template's name, and an empty name matches any
+    /// template, like `-ru-` in `= {{-ru-}} =`.
+    HeadingTemplate(&'static str, usize),
+    /// A template on a line of its own, labelled by the first word of its name, like `сущ` in
+    /// `{{сущ ru m a 1a}}` or `Synonyme` in `{{Synonyme}}`.
+    Template,
+}
+
+/// How an edition lists definitions.
+#[derive(Debug)]
+pub enum Definitions {
+    /// Lines starting with `# `.
+    Numbered,
+    /// Lines starting with `:`, usually `:[1] `, after a line with just this template.
+    Listed(&'static str),
+}
+
+/// A Wiktionary edition's markup, as `parse_wikitext` needs it.
+#[derive(Debug)]
+pub struct Edition {
+    /// The edition's language code, as in `frwiktionary`.
+    pub code: &'static str,
+    /// The source its definitions are saved under, like `frwikt`.
+    pub source: &'static str,
+    pub template_namespace: &'static str,
+    pub module_namespace: &'static str,
+    pub language_markers: &'static [Marker],
+    pub languages: Names,
+    pub part_of_speech_markers: &'static [Marker],
+    pub parts_of_speech: Names,
+    pub relation_markers: &'static [Marker],
+    /// Relations as stored in the database, like `synonym`.
+    pub relations: Names,
+    pub definitions: Definitions,
+}
+
+// Finds the label `marker` gives a heading, or a line if `heading` isn't set.
+fn label(marker: &Marker, text: &str, heading: bool) -> Option<String> {
+    match *marker {
+        Marker::Heading if heading => Some(text.trim().to_owned()),
+        Marker::HeadingTemplate(name, arg) if heading => templates(text)
+            .into_iter()
+            .find(|args| name.is_empty() || args[0].trim() == name)
+            .and_then(|args| args.get(arg).map(|arg| arg.trim().to_owned())),
+        Marker::Template if !heading => {
+            let line = text.trim();
+            let name = line.strip_prefix("{{")?;
+            // the template either ends the line or goes on to the next ones
+            let alone = (line.ends_with("}}") && templates(line).len() == 1) || !line.contains("}}");
+            if !alone {
+                return None;
+            }
+            name.split(['|', '}']).next()?.split_whitespace().next().map(|word| word.to_owned())
+        }
+        _ => None,
+    }
+}
+
+fn lookup(markers: &[Marker], names: Names, text: &str, heading: bool) -> Option<&'static str> {
+    markers.iter().filter_map(|marker| label(marker, text, heading)).find_map(|label| {
+        names
+            .iter()
+            .find(|&&(name, labels)| name == label || labels.contains(&label.as_str()))
+            .map(|&(name, _)| name)
+    })
+}
+
+impl Edition {
+    /// Finds the edition with this language code.
+    pub fn find(code: &str) -> Option<&'static Edition> {
+        EDITIONS.iter().cloned().find(|edition| edition.code == code)
+    }
+
+    /// Finds the edition whose definitions are saved under `source`.
+    pub fn of_source(source: &str) -> Option<&'static Edition> {
+        EDITIONS.iter().cloned().find(|edition| edition.source == source)
+    }
+
+    /// The language a heading starts a section for.
+    pub fn language(&self, heading: &str) -> Option<&'static str> {
+        lookup(self.language_markers, self.languages, heading, true)
+    }
+
+    /// The part of speech a heading, or a line if `heading` isn't set, starts a section for.
+    pub fn part_of_speech(&self, text: &str, heading: bool) -> Option<&'static str> {
+        lookup(self.part_of_speech_markers, self.parts_of_speech, text, heading)
+    }
+
+    /// The relation a heading, or a line if `heading` isn't set, starts a section for.
+    pub fn relation(&self, text: &str, heading: bool) -> Option<&'static str> {
+        lookup(self.relation_markers, self.relations, text, heading)
+    }
+}
+
+pub static ENGLISH: Edition = Edition {
+    code: "en",
+    source: "enwikt",
+    template_namespace: "Template",
+    module_namespace: "Module",
+    language_markers: &[Marker::Heading],
+    // TODO: figure out list of languages automatically
+    languages: &[
+        ("Alemannic German", &[]),
+        ("Chinese", &[]),
+        ("English", &[]),
+        ("Esperanto", &[]),
+        ("French", &[]),
+        ("German", &[]),
+        ("Japanese", &[]),
+        ("Korean", &[]),
+        ("Lojban", &[]),
+    ],
+    // TODO: figure out POS list automatically
+    part_of_speech_markers: &[Marker::Heading],
+    parts_of_speech: &[
+        ("Adjective", &[]),
+        ("Adverb", &[]),
+        ("Brivla", &[]),
+        ("Cmavo", &[]),
+        ("Conjunction", &[]),
+        ("Definitions", &[]),
+        ("Gismu", &[]),
+        ("Hanja", &[]),
+        ("Hanzi", &[]),
+        ("Infix", &[]),
+        ("Initialism", &[]),
+        ("Interjection", &[]),
+        ("Kanji", &[]),
+        ("Noun", &[]),
+        ("Phrase", &[]),
+        ("Proper noun", &[]),
+        ("Rafsi", &[]),
+        ("Romanization", &[]),
+        ("Verb", &[]),
+    ],
+    relation_markers: &[Marker::Heading],
+    relations: &[
+        ("synonym", &["Synonyms"]),
+        ("antonym", &["Antonyms"]),
+        ("hypernym", &["Hypernyms"]),
+        ("hyponym", &["Hyponyms"]),
+        ("meronym", &["Meronyms"]),
+        ("holonym", &["Holonyms"]),
+        ("troponym", &["Troponyms"]),
+        ("coordinate", &["Coordinate terms"]),
+        ("derived", &["Derived terms"]),
+        ("related", &["Related terms"]),
+    ],
+    definitions: Definitions::Numbered,
+};
+
+pub static FRENCH: Edition = Edition {
+    code: "fr",
+    source: "frwikt",
+    template_namespace: "Modèle",
+    module_namespace: "Module",
+    language_markers: &[Marker::HeadingTemplate("langue", 1)],
+    languages: &[
+        ("Chinese", &["zh"]),
+        ("English", &["en"]),
+        ("Esperanto", &["eo"]),
+        ("French", &["fr"]),
+        ("German", &["de"]),
+        ("Japanese", &["ja"]),
+        ("Korean", &["ko"]),
+        ("Russian", &["ru"]),
+    ],
+    part_of_speech_markers: &[Marker::HeadingTemplate("S", 1)],
+    parts_of_speech: &[
+        ("Adjective", &["adjectif"]),
+        ("Adverb", &["adverbe"]),
+        ("Conjunction", &["conjonction"]),
+        ("Infix", &["infixe"]),
+        ("Initialism", &["sigle"]),
+        ("Interjection", &["interjection"]),
+        ("Noun", &["nom"]),
+        ("Phrase", &["locution-phrase"]),
+        ("Proper noun", &["nom propre"]),
+        ("Verb", &["verbe"]),
+    ],
+    relation_markers: &[Marker::HeadingTemplate("S", 1)],
+    relations: &[
+        ("synonym", &["synonymes"]),
+        ("antonym", &["antonymes"]),
+        ("hypernym", &["hyperonymes"]),
+        ("hyponym", &["hyponymes"]),
+        ("meronym", &["méronymes"]),
+        ("holonym", &["holonymes"]),
+        ("troponym", &["troponymes"]),
+        ("derived", &["dérivés"]),
+        ("related", &["apparentés"]),
+    ],
+    definitions: Definitions::Numbered,
+};
+
+pub static GERMAN: Edition = Edition {
+    code: "de",
+    source: "dewikt",
+    template_namespace: "Vorlage",
+    module_namespace: "Modul",
+    language_markers: &[Marker::HeadingTemplate("Sprache", 1)],
+    languages: &[
+        ("Alemannic German", &["Alemannisch"]),
+        ("Chinese", &["Chinesisch"]),
+        ("English", &["Englisch"]),
+        ("Esperanto", &[]),
+        ("French", &["Französisch"]),
+        ("German", &["Deutsch"]),
+        ("Japanese", &["Japanisch"]),
+        ("Korean", &["Koreanisch"]),
+        ("Lojban", &[]),
+        ("Russian", &["Russisch"]),
+    ],
+    part_of_speech_markers: &[Marker::HeadingTemplate("Wortart", 1)],
+    parts_of_speech: &[
+        ("Adjective", &["Adjektiv"]),
+        ("Adverb", &[]),
+        ("Conjunction", &["Konjunktion"]),
+        ("Initialism", &["Initialwort"]),
+        ("Interjection", &["Interjektion"]),
+        ("Noun", &["Substantiv"]),
+        ("Phrase", &["Redewendung"]),
+        ("Proper noun", &["Eigenname"]),
+        ("Verb", &[]),
+    ],
+    relation_markers: &[Marker::Template],
+    relations: &[
+        ("synonym", &["Synonyme"]),
+        ("antonym", &["Gegenwörter"]),
+        ("hypernym", &["Oberbegriffe"]),
+        ("hyponym", &["Unterbegriffe"]),
+        ("derived", &["Wortbildungen"]),
+        ("related", &["Sinnverwandte"]),
+    ],
+    definitions: Definitions::Listed("Bedeutungen"),
+};
+
+pub static JAPANESE: Edition = Edition {
+    code: "ja",
+    source: "jawikt",
+    template_namespace: "テンプレート",
+    module_namespace: "モジュール",
+    language_markers: &[Marker::Heading, Marker::HeadingTemplate("L", 1), Marker::HeadingTemplate("", 0)],
+    languages: &[
+        ("Chinese", &["zh", "中国語"]),
+        ("English", &["en", "英語"]),
+        ("Esperanto", &["eo", "エスペラント"]),
+        ("French", &["fr", "フランス語"]),
+        ("German", &["de", "ドイツ語"]),
+        ("Japanese", &["ja", "日本語"]),
+        ("Korean", &["ko", "朝鮮語", "韓国語"]),
+        ("Russian", &["ru", "ロシア語"]),
+    ],
+    part_of_speech_markers: &[Marker::Heading, Marker::HeadingTemplate("", 0)],
+    parts_of_speech: &[
+        ("Adjective", &["adjective", "adj", "形容詞"]),
+        ("Adverb", &["adverb", "adv", "副詞"]),
+        ("Conjunction", &["conjunction", "conj", "接続詞"]),
+        ("Interjection", &["interjection", "interj", "感動詞"]),
+        ("Kanji", &["kanji", "漢字"]),
+        ("Noun", &["noun", "名詞"]),
+        ("Phrase", &["phrase", "成句"]),
+        ("Proper noun", &["proper noun", "prop", "固有名詞"]),
+        ("Verb", &["verb", "動詞"]),
+    ],
+    relation_markers: &[Marker::Heading, Marker::HeadingTemplate("", 0)],
+    relations: &[
+        ("synonym", &["syn", "類義語"]),
+        ("antonym", &["ant", "対義語"]),
+        ("hypernym", &["上位語"]),
+        ("hyponym", &["下位語"]),
+        ("derived", &["drv", "派生語"]),
+        ("related", &["rel", "関連語"]),
+    ],
+    definitions: Definitions::Numbered,
+};
+
+pub static RUSSIAN: Edition = Edition {
+    code: "ru",
+    source: "ruwikt",
+    template_namespace: "Шаблон",
+    module_namespace: "Модуль",
+    language_markers: &[Marker::HeadingTemplate("", 0)],
+    languages: &[
+        ("Chinese", &["-zh-"]),
+        ("English", &["-en-"]),
+        ("Esperanto", &["-eo-"]),
+        ("French", &["-fr-"]),
+        ("German", &["-de-"]),
+        ("Japanese", &["-ja-"]),
+        ("Korean", &["-ko-"]),
+        ("Russian", &["-ru-"]),
+    ],
+    // parts of speech are only given by the template describing the word's morphology
+    part_of_speech_markers: &[Marker::Template],
+    parts_of_speech: &[
+        ("Adjective", &["прил"]),
+        ("Adverb", &["adv"]),
+        ("Conjunction", &["conj"]),
+        ("Interjection", &["interj"]),
+        ("Noun", &["сущ"]),
+        ("Phrase", &["phrase"]),
+        ("Verb", &["гл"]),
+    ],
+    relation_markers: &[Marker::Heading],
+    relations: &[
+        ("synonym", &["Синонимы"]),
+        ("antonym", &["Антонимы"]),
+        ("hypernym", &["Гиперонимы"]),
+        ("hyponym", &["Гипонимы"]),
+        ("meronym", &["Меронимы"]),
+        ("holonym", &["Холонимы"]),
+    ],
+    definitions: Definitions::Numbered,
+};
+
+/// Every edition build_definitions_db can read.
+pub static EDITIONS: &[&Edition] = &[&ENGLISH, &FRENCH, &GERMAN, &JAPANESE, &RUSSIAN];
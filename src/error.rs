@@ -0,0 +1,231 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use datasets;
+use editions;
+use quick_xml;
+use rusqlite;
+
+#[derive(Debug)]
+pub enum Error {
+    /// There's no database at this path; it has to be built with build_definitions_db first.
+    MissingDatabase(PathBuf),
+    /// The database at this path has a schema version this version of define3 can't read, so it
+    /// has to be rebuilt (or define3 upgraded).
+    IncompatibleDatabase {
+        path: PathBuf,
+        version: u32,
+        expected: u32,
+    },
+    /// There's no interrupted build at this path for `--resume` to continue.
+    NoBuildToResume(PathBuf),
+    /// The interrupted build at this path was left broken, so it can't be resumed.
+    DamagedDatabase(PathBuf),
+    /// The interrupted build was reading another dump.
+    ResumeMismatch {
+        expected: String,
+        found: String,
+    },
+    /// A compressed dump, which has to be extracted before build_definitions_db can read it.
+    CompressedDump(PathBuf),
+    /// A dataset format build_definitions_db can't import.
+    UnknownFormat(String),
+    /// A Wiktionary edition build_definitions_db doesn't know how to read.
+    UnknownEdition(String),
+    /// The database at this path was built from another edition than the one asked for.
+    EditionMismatch {
+        path: PathBuf,
+        recorded: String,
+        requested: String,
+    },
+    /// A source filter that isn't one of the sources in the database.
+    UnknownSource(String),
+    /// A language filter that isn't a language name, code or alias in the database.
+    UnknownLanguage(String),
+    /// A part of speech filter that doesn't match any part of speech in the database.
+    UnknownPartOfSpeech(String),
+    /// The platform has no data directory to keep the database in.
+    NoDataDir,
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// The dump isn't well-formed XML, so reading can't continue.
+    Xml {
+        position: usize,
+        title: Option<String>,
+        source: quick_xml::Error,
+    },
+    /// A line of a dataset that isn't in its format.
+    MalformedLine {
+        path: PathBuf,
+        line: usize,
+    },
+    /// A page whose text couldn't be decoded. Reading can continue with the next page.
+    MalformedPage {
+        position: usize,
+        title: Option<String>,
+        source: quick_xml::Error,
+    },
+    Sqlite {
+        step: Option<&'static str>,
+        source: rusqlite::Error,
+    },
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+/// Attaches what we were doing to SQLite errors, e.g. `.step("creating the words table")`.
+pub trait SqlStep<T> {
+    fn step(self, step: &'static str) -> Result<T>;
+}
+
+impl<T> SqlStep<T> for rusqlite::Result<T> {
+    fn step(self, step: &'static str) -> Result<T> {
+        self.map_err(|source| Error::Sqlite {
+            step: Some(step),
+            source,
+        })
+    }
+}
+
+fn fmt_page(f: &mut fmt::Formatter, position: usize, title: &Option<String>) -> fmt::Result {
+    match *title {
+        Some(ref title) => write!(f, "at byte {} (page {:?})", position, title),
+        None => write!(f, "at byte {}", position),
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::MissingDatabase(ref path) => write!(
+                f,
+                "no database at {}; run build_definitions_db to create it",
+                path.display()
+            ),
+            Error::IncompatibleDatabase {
+                ref path,
+                version,
+                expected,
+            } if version < expected => write!(
+                f,
+                "{} has schema version {}, but this version of define3 needs version {}; \
+                 rebuild it with build_definitions_db",
+                path.display(),
+                version,
+                expected
+            ),
+            Error::IncompatibleDatabase {
+                ref path,
+                version,
+                expected,
+            } => write!(
+                f,
+                "{} has schema version {}, which is newer than version {} that this version of \
+                 define3 reads; upgrade define3",
+                path.display(),
+                version,
+                expected
+            ),
+            Error::NoBuildToResume(ref path) => write!(
+                f,
+                "no interrupted build at {} to resume; start a new one without --resume",
+                path.display()
+            ),
+            Error::DamagedDatabase(ref path) => write!(
+                f,
+                "the interrupted build at {} is damaged; start a new one without --resume",
+                path.display()
+            ),
+            Error::ResumeMismatch {
+                ref expected,
+                ref found,
+            } => write!(
+                f,
+                "the interrupted build was reading {}, not {}; resume it with that dump or start \
+                 a new one without --resume",
+                expected, found
+            ),
+            Error::CompressedDump(ref path) => write!(
+                f,
+                "{} is compressed; extract it and run build_definitions_db on the .xml file",
+                path.display()
+            ),
+            Error::UnknownFormat(ref format) => write!(
+                f,
+                "unknown dataset format \"{}\"; known formats are {}",
+                format,
+                datasets::FORMATS.join(", ")
+            ),
+            Error::UnknownEdition(ref code) => {
+                let codes: Vec<&str> = editions::EDITIONS.iter().map(|edition| edition.code).collect();
+                write!(f, "unknown Wiktionary edition \"{}\"; known editions are {}", code, codes.join(", "))
+            }
+            Error::EditionMismatch {
+                ref path,
+                ref recorded,
+                ref requested,
+            } => write!(
+                f,
+                "{} was built from the {} edition of Wiktionary, not {}",
+                path.display(),
+                recorded,
+                requested
+            ),
+            Error::UnknownSource(ref source) => write!(f, "unknown source \"{}\"", source),
+            Error::UnknownLanguage(ref language) => write!(f, "unknown language \"{}\"", language),
+            Error::UnknownPartOfSpeech(ref pos) => write!(f, "unknown part of speech \"{}\"", pos),
+            Error::NoDataDir => write!(f, "couldn't find a data directory for the database"),
+            Error::Io { ref path, ref source } => write!(f, "{}: {}", path.display(), source),
+            Error::Xml {
+                position,
+                ref title,
+                ref source,
+            } => {
+                write!(f, "invalid XML ")?;
+                fmt_page(f, position, title)?;
+                write!(f, ": {}", source)
+            }
+            Error::MalformedLine { ref path, line } => {
+                write!(f, "{}, line {}: malformed entry", path.display(), line)
+            }
+            Error::MalformedPage {
+                position,
+                ref title,
+                ref source,
+            } => {
+                write!(f, "malformed page ")?;
+                fmt_page(f, position, title)?;
+                write!(f, ": {}", source)
+            }
+            Error::Sqlite {
+                step: Some(step),
+                ref source,
+            } => write!(f, "database error while {}: {}", step, source),
+            Error::Sqlite {
+                step: None,
+                ref source,
+            } => write!(f, "database error: {}", source),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io { ref source, .. } => Some(source),
+            Error::Xml { ref source, .. } | Error::MalformedPage { ref source, .. } => Some(source),
+            Error::Sqlite { ref source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(source: rusqlite::Error) -> Error {
+        Error::Sqlite { step: None, source }
+    }
+}
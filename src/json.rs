@@ -0,0 +1,77 @@
+//! The JSON format printed by `define --json` and served by define3-server, documented in the
+//! README.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use {Meaning, Relation};
+
+// language -> relation -> (sense, target)
+type Relations<'a> = BTreeMap<&'a str, BTreeMap<&'a str, Vec<(Option<&'a str>, &'a str)>>>;
+
+/// Builds the JSON object for `word` from its meanings and relations, passing each definition
+/// and sense through `format` first.
+pub fn word<F>(word: &str, meanings: &[Meaning], relations: &[Relation], mut format: F) -> Value
+where
+    F: FnMut(&str) -> String,
+{
+    let mut langs: BTreeMap<&str, BTreeMap<&str, Vec<&str>>> = BTreeMap::new();
+    for meaning in meanings {
+        langs
+            .entry(&meaning.language)
+            .or_default()
+            .entry(&meaning.part_of_speech)
+            .or_default()
+            .push(&meaning.definition);
+    }
+    let mut lang_relations: Relations = BTreeMap::new();
+    for relation in relations {
+        let targets = lang_relations
+            .entry(&relation.language)
+            .or_default()
+            .entry(&relation.relation)
+            .or_default();
+        let entry = (relation.sense.as_deref(), relation.target.as_str());
+        if !targets.contains(&entry) {
+            targets.push(entry);
+        }
+    }
+
+    let mut all_langs: Vec<&str> = langs.keys().chain(lang_relations.keys()).cloned().collect();
+    all_langs.sort();
+    all_langs.dedup();
+
+    let languages: Vec<Value> = all_langs.iter().map(|lang| {
+        let poses: Vec<Value> = langs.get(lang).into_iter().flatten()
+            .map(|(pos, defns)| json!({
+                "part_of_speech": pos,
+                "definitions": defns.iter().map(|defn| format(defn)).collect::<Vec<_>>(),
+            }))
+            .collect();
+        let mut lang_json = json!({
+            "language": lang,
+            "parts_of_speech": poses,
+        });
+        if let Some(relations) = lang_relations.get(lang) {
+            let relations: Vec<Value> = relations
+                .iter()
+                .flat_map(|(relation, targets)| targets.iter().map(move |(sense, target)| {
+                    (relation, sense, target)
+                }))
+                .map(|(relation, sense, target)| json!({
+                    "relation": relation,
+                    "target": target,
+                    "sense": sense.map(&mut format),
+                }))
+                .collect();
+            lang_json["relations"] = json!(relations);
+        }
+        lang_json
+    }).collect();
+
+    json!({
+        "word": word,
+        "languages": languages,
+    })
+}
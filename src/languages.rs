@@ -0,0 +1,260 @@
+//! Language data from the dump's `Module:languages/data...` Lua modules, which map Wiktionary's
+//! language codes to the names used as headings.
+//!
+//! The modules are big table constructors like
+//!
+//! ```lua
+//! m["gsw"] = {
+//!     "Alemannic German",
+//!     131339,
+//!     "gmw-hgm",
+//!     "Latn",
+//!     otherNames = {"Alsatian", "Swiss German"},
+//! }
+//! ```
+//!
+//! Older dumps name every field (`canonicalName = "English"`). We only understand enough Lua to
+//! read these tables.
+
+/// A language as described by the language data modules.
+#[derive(Debug, PartialEq)]
+pub struct Language {
+    pub code: String,
+    pub canonical_name: String,
+    pub family: Option<String>,
+    pub scripts: Vec<String>,
+    /// Other names the language goes by.
+    pub aliases: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Str(String),
+    Number,
+    Name(String),
+    Punct(char),
+}
+
+#[derive(Debug)]
+enum Value {
+    Str(String),
+    Table(Vec<(Option<String>, Value)>),
+    // numbers, booleans, function calls and whatever else we don't need
+    Other,
+}
+
+// Reads the string starting at `start` in a `[[long bracket]]`, `[==[like this]==]`, returning
+// its contents and where it ends.
+fn long_bracket(src: &str, start: usize) -> Option<(String, usize)> {
+    // `start` can be past the end, or inside a character, after a `--` comment
+    let rest = src.get(start..)?.strip_prefix('[')?;
+    let level = rest.find(|c| c != '=')?;
+    if !rest[level..].starts_with('[') {
+        return None;
+    }
+    let close = format!("]{}]", "=".repeat(level));
+    let contents_start = start + 1 + level + 1;
+    let len = src[contents_start..].find(&close)?;
+    Some((src[contents_start..contents_start + len].to_owned(), contents_start + len + close.len()))
+}
+
+fn tokenize(src: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = src.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '-' if src[i..].starts_with("--") => {
+                // a comment, either --[[ long ]] or to the end of the line
+                let end = match long_bracket(src, i + 2) {
+                    Some((_, end)) => end,
+                    None => src[i..].find('\n').map_or(src.len(), |len| i + len),
+                };
+                while chars.peek().is_some_and(|&(j, _)| j < end) {
+                    chars.next();
+                }
+            }
+            '[' if src[i..].starts_with("[[") || src[i..].starts_with("[=") => {
+                if let Some((s, end)) = long_bracket(src, i) {
+                    tokens.push(Token::Str(s));
+                    while chars.peek().is_some_and(|&(j, _)| j < end) {
+                        chars.next();
+                    }
+                } else {
+                    tokens.push(Token::Punct(c));
+                }
+            }
+            '"' | '\'' => {
+                let mut s = String::new();
+                while let Some((_, d)) = chars.next() {
+                    match d {
+                        '\\' => match chars.next() {
+                            Some((_, 'n')) => s.push('\n'),
+                            Some((_, 't')) => s.push('\t'),
+                            Some((_, e)) => s.push(e),
+                            None => break,
+                        },
+                        d if d == c => break,
+                        d => s.push(d),
+                    }
+                }
+                tokens.push(Token::Str(s));
+            }
+            c if c.is_ascii_digit() => {
+                while chars.peek().is_some_and(|&(_, d)| d.is_ascii_alphanumeric() || d == '.') {
+                    chars.next();
+                }
+                tokens.push(Token::Number);
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut name = c.to_string();
+                while let Some(&(_, d)) = chars.peek() {
+                    if !(d.is_alphanumeric() || d == '_') {
+                        break;
+                    }
+                    name.push(d);
+                    chars.next();
+                }
+                tokens.push(Token::Name(name));
+            }
+            c if c.is_whitespace() => (),
+            c => tokens.push(Token::Punct(c)),
+        }
+    }
+    tokens
+}
+
+// Parses the table constructor whose `{` is at `tokens[*pos]`, leaving `*pos` after its `}`.
+fn table(tokens: &[Token], pos: &mut usize) -> Value {
+    let mut fields = Vec::new();
+    *pos += 1;
+    while *pos < tokens.len() {
+        match (&tokens[*pos], tokens.get(*pos + 1)) {
+            (Token::Punct('}'), _) => {
+                *pos += 1;
+                break;
+            }
+            (Token::Punct(','), _) | (Token::Punct(';'), _) => *pos += 1,
+            (Token::Name(key), Some(Token::Punct('='))) => {
+                *pos += 2;
+                fields.push((Some(key.clone()), value(tokens, pos)));
+            }
+            (Token::Punct('['), Some(Token::Str(key))) => {
+                // ["key"] = value
+                *pos += 4;
+                fields.push((Some(key.clone()), value(tokens, pos)));
+            }
+            _ => fields.push((None, value(tokens, pos))),
+        }
+    }
+    Value::Table(fields)
+}
+
+// Parses the expression at `tokens[*pos]`. Anything but a string or table is skipped up to the
+// next field.
+fn value(tokens: &[Token], pos: &mut usize) -> Value {
+    match tokens.get(*pos) {
+        Some(Token::Str(s)) => {
+            *pos += 1;
+            Value::Str(s.clone())
+        }
+        Some(Token::Punct('{')) => table(tokens, pos),
+        _ => {
+            let mut depth = 0;
+            while let Some(token) = tokens.get(*pos) {
+                match *token {
+                    Token::Punct('{') | Token::Punct('(') => depth += 1,
+                    Token::Punct('}') | Token::Punct(')') if depth == 0 => break,
+                    Token::Punct('}') | Token::Punct(')') => depth -= 1,
+                    Token::Punct(',') | Token::Punct(';') if depth == 0 => break,
+                    _ => (),
+                }
+                *pos += 1;
+            }
+            Value::Other
+        }
+    }
+}
+
+fn strings(value: &Value) -> Vec<String> {
+    match *value {
+        // scripts are sometimes given as one string, like "Latn, Cyrl"
+        Value::Str(ref s) => s
+            .split(',')
+            .map(|s| s.trim().to_owned())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        Value::Table(ref fields) => fields
+            .iter()
+            .filter_map(|(_, value)| match value {
+                Value::Str(s) => Some(s.clone()),
+                _ => None,
+            })
+            .collect(),
+        Value::Other => Vec::new(),
+    }
+}
+
+fn language(code: &str, fields: &[(Option<String>, Value)]) -> Option<Language> {
+    let mut positional = fields.iter().filter(|(key, _)| key.is_none()).map(|(_, value)| value);
+    let named = |name: &str| {
+        fields
+            .iter()
+            .find(|(key, _)| key.as_deref() == Some(name))
+            .map(|(_, value)| value)
+    };
+    let first = positional.next();
+    let _wikidata_item = positional.next();
+    let third = positional.next();
+    let fourth = positional.next();
+
+    let canonical_name = match (named("canonicalName"), first) {
+        (Some(Value::Str(name)), _) | (None, Some(Value::Str(name))) => name.clone(),
+        _ => return None,
+    };
+    let family = match named("family").or(third) {
+        Some(Value::Str(family)) => Some(family.clone()),
+        _ => None,
+    };
+    let scripts = named("scripts").or(fourth).map(strings).unwrap_or_default();
+    let mut aliases = Vec::new();
+    for field in &["otherNames", "aliases"] {
+        if let Some(value) = named(field) {
+            aliases.extend(strings(value));
+        }
+    }
+    Some(Language {
+        code: code.to_owned(),
+        canonical_name,
+        family,
+        scripts,
+        aliases,
+    })
+}
+
+/// Finds the languages defined by `m["code"] = { ... }` in a language data module.
+pub fn parse_module(src: &str) -> Vec<Language> {
+    let tokens = tokenize(src);
+    let mut languages = Vec::new();
+    let mut pos = 0;
+    while pos + 5 < tokens.len() {
+        let code = match &tokens[pos..pos + 6] {
+            [Token::Name(_), Token::Punct('['), Token::Str(code), Token::Punct(']'), Token::Punct('='), Token::Punct('{')] => {
+                code
+            }
+            _ => {
+                pos += 1;
+                continue;
+            }
+        };
+        pos += 5;
+        if let Value::Table(ref fields) = table(&tokens, &mut pos) {
+            languages.extend(language(code, fields));
+        }
+    }
+    languages
+}
+
+/// Whether the module called `Module:name` has language data.
+pub fn is_data_module(name: &str) -> bool {
+    name.starts_with("languages/data") && !name.ends_with("/documentation")
+}
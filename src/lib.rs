@@ -1,23 +1,102 @@
+extern crate colored;
+extern crate dirs;
+extern crate quick_xml;
+extern crate regex;
+extern crate rusqlite;
+#[macro_use]
+extern crate serde_json;
+extern crate textwrap;
+
+pub mod datasets;
+pub mod dictionary;
+pub mod editions;
+pub mod error;
+pub mod json;
+pub mod languages;
+pub mod markup;
 pub mod parse_xml;
 pub mod parse_wikitext;
+pub mod render;
+pub mod server;
+pub mod templates;
+pub mod wiktextract;
 
-#[derive(Debug)]
+pub use dictionary::{Dictionary, Filters};
+pub use error::{Error, Result};
+
+use std::borrow::Cow;
+
+#[derive(Debug, Clone)]
 pub struct Meaning {
     pub language: String,
     pub part_of_speech: String,
     pub definition: String,
+    /// The dictionary the meaning comes from, like `enwikt`.
+    pub source: String,
+}
+
+/// A semantic relation from a word to another, such as a synonym or a derived term.
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub language: String,
+    pub relation: String,
+    pub target: String,
+    pub sense: Option<String>,
+    /// The dictionary the relation comes from, like `enwikt`.
+    pub source: String,
+}
+
+/// A link from one of a word's definitions to another page, like `[[chat#French|cat]]`.
+#[derive(Debug)]
+pub struct Link {
+    pub language: String,
+    pub target: String,
+    pub anchor: Option<String>,
+    pub text: String,
 }
 
 #[derive(Debug)]
 pub struct Page {
     pub title: String,
+    /// The page's id, which stays the same when it's edited or renamed.
+    pub id: Option<i64>,
+    /// The id of the revision the content is from.
+    pub revision_id: Option<i64>,
+    /// The page this one redirects to, if it's a redirect.
+    pub redirect: Option<String>,
+    /// Empty if the revision has no text, like when it was deleted.
     pub content: String,
 }
 
+/// A page read from a dump, borrowing from the reader's buffers until the next page is read.
+/// Text without escapes is borrowed as it is.
+#[derive(Debug)]
+pub struct PageRef<'a> {
+    pub title: Cow<'a, str>,
+    pub id: Option<i64>,
+    pub revision_id: Option<i64>,
+    pub redirect: Option<String>,
+    /// Empty if the revision has no text, like when it was deleted.
+    pub content: Cow<'a, str>,
+}
+
+impl<'a> PageRef<'a> {
+    pub fn into_owned(self) -> Page {
+        Page {
+            title: self.title.into_owned(),
+            id: self.id,
+            revision_id: self.revision_id,
+            redirect: self.redirect,
+            content: self.content.into_owned(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Word {
     pub name: String,
     pub meanings: Vec<Meaning>,
+    pub relations: Vec<Relation>,
 }
 
 #[derive(Debug)]
@@ -32,9 +111,17 @@ pub struct Module {
     pub src: String,
 }
 
+/// A page that only sends readers to another one, like `#REDIRECT [[cat]]`.
+#[derive(Debug)]
+pub struct Redirect {
+    pub title: String,
+    pub target: String,
+}
+
 #[derive(Debug)]
 pub enum PageContent {
     Word(Word),
     Template(Template),
     Module(Module),
+    Redirect(Redirect),
 }
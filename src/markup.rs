@@ -0,0 +1,108 @@
+//! The inline wikitext markup kept in definitions, such as `[[links]]` and `'''bold'''`.
+
+use markup::Span::*;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Span<'a> {
+    Text(&'a str),
+    /// `[[target#anchor|text]]`. Without the `|`, the text is everything inside the brackets.
+    Link {
+        target: &'a str,
+        anchor: Option<&'a str>,
+        text: &'a str,
+    },
+    /// `'''`, which turns bold on or off.
+    Bold,
+    /// `''`, which turns italics on or off.
+    Italic,
+}
+
+fn link<'a>(inner: &'a str) -> Span<'a> {
+    let (page, text) = match inner.find('|') {
+        Some(i) => (&inner[..i], &inner[i + 1..]),
+        None => (inner, inner),
+    };
+    let (target, anchor) = match page.find('#') {
+        Some(i) => (&page[..i], Some(&page[i + 1..])),
+        None => (page, None),
+    };
+    Link {
+        target: target.trim(),
+        anchor,
+        text,
+    }
+}
+
+// Pushes `text`, splitting out its bold and italic markers. Like MediaWiki, four apostrophes are
+// an apostrophe and a bold marker, and more than five are apostrophes and both markers.
+fn push_text<'a>(result: &mut Vec<Span<'a>>, text: &'a str) {
+    let mut rest = text;
+    while let Some(start) = rest.find("''") {
+        let len = rest[start..].find(|c| c != '\'').unwrap_or(rest.len() - start);
+        let (literal, markers): (usize, &[Span]) = match len {
+            2 => (0, &[Italic]),
+            3 => (0, &[Bold]),
+            4 => (1, &[Bold]),
+            _ => (len - 5, &[Bold, Italic]),
+        };
+        if start + literal > 0 {
+            result.push(Text(&rest[..start + literal]));
+        }
+        result.extend_from_slice(markers);
+        rest = &rest[start + len..];
+    }
+    if !rest.is_empty() {
+        result.push(Text(rest));
+    }
+}
+
+/// Splits `text` into plain text, links, and bold and italic markers.
+pub fn spans<'a>(text: &'a str) -> Vec<Span<'a>> {
+    let mut result = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("[[") {
+        let inner_start = start + 2;
+        let len = match rest[inner_start..].find("]]") {
+            Some(len) => len,
+            None => break,
+        };
+        push_text(&mut result, &rest[..start]);
+        result.push(link(&rest[inner_start..inner_start + len]));
+        rest = &rest[inner_start + len + 2..];
+    }
+    push_text(&mut result, rest);
+    result
+}
+
+/// Replaces every link in `text` with what `f` returns for its target, anchor and text.
+pub fn replace_links<F>(text: &str, mut f: F) -> String
+where
+    F: FnMut(&str, Option<&str>, &str) -> String,
+{
+    let mut result = String::with_capacity(text.len());
+    for span in spans(text) {
+        match span {
+            Text(text) => result.push_str(text),
+            Link {
+                target,
+                anchor,
+                text,
+            } => result.push_str(&f(target, anchor, text)),
+            Bold => result.push_str("'''"),
+            Italic => result.push_str("''"),
+        }
+    }
+    result
+}
+
+/// Replaces every link in `text` with the text it displays, and drops bold and italics.
+pub fn strip_links(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for span in spans(text) {
+        match span {
+            Text(text) | Link { text, .. } => result.push_str(text),
+            Bold | Italic => (),
+        }
+    }
+    result
+}
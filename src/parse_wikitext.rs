@@ -1,6 +1,6 @@
-use std::collections::HashSet;
-
-use Meaning;
+use editions::{Definitions, Edition};
+use markup::{self, Span};
+use {Link, Meaning, Relation};
 
 #[derive(Debug, PartialEq)]
 pub enum WikiContext {
@@ -16,67 +16,130 @@ use parse_wikitext::WikiContext::*;
 
 impl WikiContext {
     pub fn precedence(&self) -> u32 {
-        match self {
-            &Heading1(_) => 1,
-            &Heading2(_) => 2,
-            &Heading3(_) => 3,
-            &Heading4(_) => 4,
-            &Heading5(_) => 5,
-            &Heading6(_) => 6,
+        match *self {
+            Heading1(_) => 1,
+            Heading2(_) => 2,
+            Heading3(_) => 3,
+            Heading4(_) => 4,
+            Heading5(_) => 5,
+            Heading6(_) => 6,
         }
     }
 
     pub fn text(&self) -> &String {
-        match self {
-            &Heading1(ref x) => &x,
-            &Heading2(ref x) => &x,
-            &Heading3(ref x) => &x,
-            &Heading4(ref x) => &x,
-            &Heading5(ref x) => &x,
-            &Heading6(ref x) => &x,
+        match *self {
+            Heading1(ref x) => x,
+            Heading2(ref x) => x,
+            Heading3(ref x) => x,
+            Heading4(ref x) => x,
+            Heading5(ref x) => x,
+            Heading6(ref x) => x,
         }
     }
 }
 
+/// Maps an inline template like `{{syn|en|...}}` to the relation it introduces.
+pub fn relation_of_template(name: &str) -> Option<&'static str> {
+    match name {
+        "syn" | "synonyms" => Some("synonym"),
+        "ant" | "antonyms" => Some("antonym"),
+        "hyper" | "hypernyms" => Some("hypernym"),
+        "hypo" | "hyponyms" => Some("hyponym"),
+        "mero" | "meronyms" => Some("meronym"),
+        "holo" | "holonyms" => Some("holonym"),
+        "tropo" | "troponyms" => Some("troponym"),
+        "cot" | "coordinate terms" => Some("coordinate"),
+        _ => None,
+    }
+}
+
 pub struct ContextStack {
     contexts: Vec<WikiContext>,
-    pub language: Option<String>,
-    pub part_of_speech: Option<String>,
+    pub language: Option<&'static str>,
+    pub part_of_speech: Option<&'static str>,
+    pub relation: Option<&'static str>,
+    /// Whether the lines that follow are definitions, in editions that list them after a
+    /// template like `{{Bedeutungen}}`.
+    pub definitions: bool,
+    // the precedence of whatever set the language, part of speech and relation; templates on
+    // lines of their own don't have one of their own
+    levels: [u32; 3],
 }
 
+// Lasts until the next heading or template on a line of its own.
+const TEMPLATE_LEVEL: u32 = u32::MAX;
+
 impl ContextStack {
-    pub fn apply(
-        &mut self,
-        context: WikiContext,
-        languages: &HashSet<&str>,
-        parts_of_speech: &HashSet<&str>,
-    ) {
+    pub fn apply(&mut self, context: WikiContext, edition: &Edition) {
         let new_prec = context.precedence();
         // leave only lower-precedence contexts in the stack
-        let contexts = &mut self.contexts;
-        while contexts
+        while self
+            .contexts
             .last()
-            .map_or(false, |c| c.precedence() >= new_prec)
+            .is_some_and(|c| c.precedence() >= new_prec)
         {
-            match contexts.pop() {
-                None => (),
-                Some(context) => {
-                    if self.language.as_ref().unwrap_or(&String::from("")) == context.text() {
-                        self.language = None;
-                    }
-                    if self.part_of_speech.as_ref().unwrap_or(&String::from("")) == context.text() {
-                        self.part_of_speech = None;
-                    }
-                }
-            }
+            self.contexts.pop();
+        }
+        self.clear(new_prec);
+        let text = context.text();
+        if let Some(language) = edition.language(text) {
+            self.language = Some(language);
+            self.levels[0] = new_prec;
+        } else if let Some(part_of_speech) = edition.part_of_speech(text, true) {
+            self.part_of_speech = Some(part_of_speech);
+            self.levels[1] = new_prec;
+        } else if let Some(relation) = edition.relation(text, true) {
+            self.relation = Some(relation);
+            self.levels[2] = new_prec;
+        }
+        self.contexts.push(context);
+    }
+
+    /// Applies a line that's just a template, returning whether it started a section like
+    /// `{{Synonyme}}`.
+    pub fn apply_template(&mut self, line: &str, edition: &Edition) -> bool {
+        if let Some(part_of_speech) = edition.part_of_speech(line, false) {
+            // lasts until the next language, since it's said once for all of its sections
+            self.part_of_speech = Some(part_of_speech);
+            self.levels[1] = self.levels[0] + 1;
+            return true;
         }
-        if languages.contains(context.text().as_str()) {
-            self.language = Some(context.text().clone());
+        if let Some(relation) = edition.relation(line, false) {
+            self.clear(TEMPLATE_LEVEL);
+            self.relation = Some(relation);
+            self.levels[2] = TEMPLATE_LEVEL;
+            return true;
         }
-        if parts_of_speech.contains(context.text().as_str()) {
-            self.part_of_speech = Some(context.text().clone());
+        let block = match edition.definitions {
+            Definitions::Listed(block) => block,
+            Definitions::Numbered => return false,
+        };
+        let line = line.trim();
+        if line == format!("{{{{{}}}}}", block) {
+            self.clear(TEMPLATE_LEVEL);
+            self.definitions = true;
+            true
+        } else if line.starts_with("{{") && line.ends_with("}}") && !line[2..].contains(['{', '|']) {
+            // the next section, like `{{Herkunft}}`
+            self.clear(TEMPLATE_LEVEL);
+            true
+        } else {
+            false
         }
-        contexts.push(context);
+    }
+
+    // Forgets whatever was set at `prec` or below.
+    fn clear(&mut self, prec: u32) {
+        if self.levels[0] >= prec {
+            self.language = None;
+        }
+        if self.levels[1] >= prec {
+            self.part_of_speech = None;
+        }
+        if self.levels[2] >= prec {
+            self.relation = None;
+        }
+        self.definitions = false;
     }
 
     pub fn new() -> ContextStack {
@@ -84,54 +147,307 @@ impl ContextStack {
             contexts: Vec::new(),
             language: None,
             part_of_speech: None,
+            relation: None,
+            definitions: false,
+            levels: [0; 3],
         }
     }
 }
 
-pub fn parse_wikitext(
-    text: String,
-    languages: &HashSet<&str>,
-    parts_of_speech: &HashSet<&str>,
-) -> Vec<Meaning> {
+impl Default for ContextStack {
+    fn default() -> ContextStack {
+        ContextStack::new()
+    }
+}
+
+/// Returns the index of the `}}` closing the template that opens at `start`.
+fn template_end(text: &str, start: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut depth = 0;
+    let mut i = start;
+    while i + 1 < bytes.len() {
+        match &bytes[i..i + 2] {
+            b"{{" => {
+                depth += 1;
+                i += 2;
+            }
+            b"}}" => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Splits the inside of a template on the `|`s that aren't nested in another template or link.
+fn template_args(inner: &str) -> Vec<&str> {
+    let bytes = inner.as_bytes();
+    let mut args = Vec::new();
+    let mut depth = 0i32;
+    let mut arg_start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth -= 1,
+            b'|' if depth == 0 => {
+                args.push(&inner[arg_start..i]);
+                arg_start = i + 1;
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    args.push(&inner[arg_start..]);
+    args
+}
+
+/// Returns the arguments of every top-level template in `text`, e.g. `{{l|en|cat}}` becomes
+/// `["l", "en", "cat"]`.
+pub fn templates(text: &str) -> Vec<Vec<&str>> {
+    let mut result = Vec::new();
+    let mut i = 0;
+    while let Some(offset) = text[i..].find("{{") {
+        let start = i + offset;
+        match template_end(text, start) {
+            Some(end) => {
+                result.push(template_args(&text[start + 2..end]));
+                i = end + 2;
+            }
+            None => break,
+        }
+    }
+    result
+}
+
+/// Returns the target page of every `[[link]]` in `text`, without any `#anchor`.
+pub fn link_targets(text: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("[[") {
+        rest = &rest[start + 2..];
+        match rest.find("]]") {
+            Some(end) => {
+                let inner = &rest[..end];
+                let target = inner.split('|').next().unwrap_or(inner);
+                let target = target.split('#').next().unwrap_or(target);
+                result.push(target.trim());
+                rest = &rest[end + 2..];
+            }
+            None => break,
+        }
+    }
+    result
+}
+
+/// Collects the words named by a template argument, which is either a bare word (possibly
+/// followed by `<q:...>` inline modifiers) or wikitext containing links.
+fn push_targets<'a>(arg: &'a str, targets: &mut Vec<&'a str>) {
+    if arg.contains('=') && !arg.contains("[[") {
+        // named parameter like q1=informal
+        return;
+    }
+    if arg.contains("[[") {
+        targets.extend(link_targets(arg));
+    } else {
+        targets.push(arg.split('<').next().unwrap_or(arg).trim());
+    }
+}
+
+/// Finds the words listed on a line of a relation section like "Synonyms", along with the sense
+/// they're given for, if any.
+fn relation_line_targets(line: &str) -> (Option<&str>, Vec<&str>) {
+    let mut sense = None;
+    let mut targets = Vec::new();
+    for args in templates(line) {
+        match args[0].trim() {
+            "sense" | "s" => sense = args.get(1).map(|s| s.trim()),
+            "l" | "l-self" | "ll" | "m" => {
+                if let Some(arg) = args.get(2) {
+                    push_targets(arg, &mut targets);
+                }
+            }
+            "col" | "col1" | "col2" | "col3" | "col4" | "col5" | "col-auto" | "col-u" | "der2"
+            | "der3" | "der4" | "der5" | "rel2" | "rel3" | "rel4" | "rel5" => {
+                for arg in args.iter().skip(2) {
+                    push_targets(arg, &mut targets);
+                }
+            }
+            _ => (),
+        }
+    }
+    // links outside templates, e.g. "* [[moggy]], [[puss]]"
+    let mut i = 0;
+    while i < line.len() {
+        let (segment_end, next) = match line[i..].find("{{") {
+            Some(offset) => {
+                let start = i + offset;
+                (start, template_end(line, start).map_or(line.len(), |end| end + 2))
+            }
+            None => (line.len(), line.len()),
+        };
+        targets.extend(link_targets(&line[i..segment_end]));
+        i = next;
+    }
+    (sense, targets)
+}
+
+/// Finds the links in a definition of a word in `language`, including those made by templates
+/// like `{{l|en|cat}}`.
+pub fn definition_links(language: &str, definition: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    for span in markup::spans(definition) {
+        if let Span::Link {
+            target,
+            anchor,
+            text,
+        } = span
+        {
+            links.push(Link {
+                language: language.to_owned(),
+                target: target.to_owned(),
+                anchor: anchor.map(|a| a.to_owned()),
+                text: text.to_owned(),
+            });
+        }
+    }
+    for args in templates(definition) {
+        match args[0].trim() {
+            "l" | "l-self" | "ll" | "m" => (),
+            _ => continue,
+        }
+        let target = match args.get(2) {
+            // links inside the template were found above
+            Some(target) if !target.contains("[[") && !target.contains('=') => target.trim(),
+            _ => continue,
+        };
+        if target.is_empty() {
+            continue;
+        }
+        let text = args
+            .get(3)
+            .map(|text| text.trim())
+            .filter(|text| !text.is_empty() && !text.contains('='))
+            .unwrap_or(target);
+        links.push(Link {
+            language: language.to_owned(),
+            target: target.to_owned(),
+            anchor: None,
+            text: text.to_owned(),
+        });
+    }
+    links
+}
+
+fn is_relation_target(target: &str) -> bool {
+    !target.is_empty() && !target.starts_with("Thesaurus:") && !target.starts_with("Wikisaurus:")
+}
+
+// Strips the sense number from a line of a German-style list, like `:[1] `.
+fn strip_sense_number(line: &str) -> &str {
+    let line = line[1..].trim_start();
+    match line.strip_prefix('[').and_then(|rest| rest.find(']').map(|end| &rest[end + 1..])) {
+        Some(rest) => rest.trim_start(),
+        None => line,
+    }
+}
+
+/// Finds the definitions and relations on a page from `edition`.
+pub fn parse_wikitext(text: &str, edition: &Edition) -> (Vec<Meaning>, Vec<Relation>) {
     let mut result: Vec<Meaning> = Vec::new();
+    let mut relations: Vec<Relation> = Vec::new();
     let mut context_stack: ContextStack = ContextStack::new();
+    // the definition that inline relations like {{syn|...}} on "#:" lines refer to
+    let mut last_definition: Option<String> = None;
 
     let stack_apply = |context_stack: &mut ContextStack, wiki_context: &dyn Fn(String) -> WikiContext, line: &str, slice: &Option<&str>| {
         slice.map_or_else(|| {
             println!("Could not parse line: {}", line);
         }, |slice| {
-            context_stack.apply(
-                wiki_context(slice.to_owned()),
-                languages,
-                parts_of_speech,
-            );
+            context_stack.apply(wiki_context(slice.to_owned()), edition);
         });
     };
 
     for line in text.lines() {
+        if line.starts_with('=') {
+            last_definition = None;
+        }
+        let definition = match edition.definitions {
+            Definitions::Numbered => line.strip_prefix("# "),
+            Definitions::Listed(_) if context_stack.definitions && line.starts_with(':') => {
+                Some(strip_sense_number(line))
+            }
+            Definitions::Listed(_) => None,
+        };
         if line.starts_with("======") && line.len() > 12 {
-            stack_apply(&mut context_stack, &|x| Heading6(x), line, &line.get(6..line.len()-6));
+            stack_apply(&mut context_stack, &Heading6, line, &line.get(6..line.len()-6));
         } else if line.starts_with("=====") && line.len() > 10 {
-            stack_apply(&mut context_stack, &|x| Heading5(x), line, &line.get(5..line.len()-5));
+            stack_apply(&mut context_stack, &Heading5, line, &line.get(5..line.len()-5));
         } else if line.starts_with("====") && line.len() > 8 {
-            stack_apply(&mut context_stack, &|x| Heading4(x), line, &line.get(4..line.len()-4));
+            stack_apply(&mut context_stack, &Heading4, line, &line.get(4..line.len()-4));
         } else if line.starts_with("===") && line.len() > 6 {
-            stack_apply(&mut context_stack, &|x| Heading3(x), line, &line.get(3..line.len()-3));
+            stack_apply(&mut context_stack, &Heading3, line, &line.get(3..line.len()-3));
         } else if line.starts_with("==") && line.len() > 4 {
-            stack_apply(&mut context_stack, &|x| Heading2(x), line, &line.get(2..line.len()-2));
-        } else if line.starts_with("=") && line.len() > 2 {
-            stack_apply(&mut context_stack, &|x| Heading1(x), line, &line.get(1..line.len()-1));
-        } else if line.starts_with("# ") {
-            context_stack.language.as_ref().and_then(|language| {
-                context_stack.part_of_speech.as_ref().map(|part_of_speech| {
-                    result.push(Meaning {
-                        language: language.clone(),
-                        part_of_speech: part_of_speech.clone(),
-                        definition: String::from(&line[2..]),
-                    })
+            stack_apply(&mut context_stack, &Heading2, line, &line.get(2..line.len()-2));
+        } else if line.starts_with('=') && line.len() > 2 {
+            stack_apply(&mut context_stack, &Heading1, line, &line.get(1..line.len()-1));
+        } else if line.starts_with("{{") && context_stack.apply_template(line, edition) {
+            // a section started by a template, like {{Synonyme}}
+        } else if context_stack.relation.is_some()
+            && (line.starts_with('*') || line.starts_with("{{") || line.starts_with("# ") || line.starts_with(':'))
+        {
+            // list items, or column templates like {{col3|en|...}}
+            if let (Some(language), Some(relation)) = (context_stack.language, context_stack.relation) {
+                let line = if line.starts_with(':') { strip_sense_number(line) } else { line };
+                let (sense, targets) = relation_line_targets(line);
+                for target in targets.into_iter().filter(|t| is_relation_target(t)) {
+                    relations.push(Relation {
+                        language: language.to_owned(),
+                        relation: relation.to_owned(),
+                        target: target.to_owned(),
+                        sense: sense.map(|s| s.to_owned()),
+                        source: edition.source.to_owned(),
+                    });
+                }
+            }
+        } else if let Some(definition) = definition {
+            last_definition = Some(definition.to_owned());
+            if let (Some(language), Some(part_of_speech)) =
+                (context_stack.language, context_stack.part_of_speech)
+            {
+                result.push(Meaning {
+                    language: language.to_owned(),
+                    part_of_speech: part_of_speech.to_owned(),
+                    definition: String::from(definition),
+                    source: edition.source.to_owned(),
                 })
-            });
+            }
+        } else if line.starts_with("#:") {
+            if let Some(language) = context_stack.language {
+                for args in templates(line) {
+                    if let Some(relation) = relation_of_template(args[0].trim()) {
+                        let mut targets = Vec::new();
+                        for arg in args.iter().skip(2) {
+                            push_targets(arg, &mut targets);
+                        }
+                        for target in targets.into_iter().filter(|t| is_relation_target(t)) {
+                            relations.push(Relation {
+                                language: language.to_owned(),
+                                relation: relation.to_owned(),
+                                target: target.to_owned(),
+                                sense: last_definition.clone(),
+                                source: edition.source.to_owned(),
+                            });
+                        }
+                    }
+                }
+            }
         }
     }
-    result
+    (result, relations)
 }
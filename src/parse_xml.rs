@@ -1,83 +1,298 @@
 extern crate quick_xml;
 
 use parse_xml::quick_xml::Reader;
+use parse_xml::quick_xml::escape::unescape;
 use parse_xml::quick_xml::events::Event;
 
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::Path;
-use std::io::BufRead;
 
-use Page;
+use error::{Error, Result};
+use PageRef;
 
-fn parse_revision<B: BufRead>(reader: &mut Reader<B>) -> Option<String> {
-    let mut buf = Vec::new();
-    let mut result = None;
+// The reader can't recover from an error in the XML itself, so these end the whole dump.
+fn xml_error<B: BufRead>(
+    reader: &Reader<B>,
+    title: Option<&str>,
+    source: quick_xml::Error,
+) -> Error {
+    Error::Xml {
+        position: reader.buffer_position(),
+        title: title.map(str::to_owned),
+        source,
+    }
+}
+
+fn malformed_page<B: BufRead>(
+    reader: &Reader<B>,
+    title: Option<&str>,
+    source: quick_xml::Error,
+) -> Error {
+    Error::MalformedPage {
+        position: reader.buffer_position(),
+        title: title.map(str::to_owned),
+        source,
+    }
+}
+
+// Reads the number in an element like <id>, if it is one.
+fn parse_id<B: BufRead>(reader: &mut Reader<B>, buf: &mut Vec<u8>) -> Option<i64> {
+    buf.clear();
+    match reader.read_event(buf) {
+        Ok(Event::Text(e)) => std::str::from_utf8(&e).ok()?.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+// Unescapes text, borrowing it if it has nothing to unescape.
+fn decode(escaped: &[u8]) -> quick_xml::Result<Cow<'_, str>> {
+    Ok(match unescape(escaped).map_err(quick_xml::Error::EscapeError)? {
+        Cow::Borrowed(bytes) => Cow::Borrowed(std::str::from_utf8(bytes).map_err(quick_xml::Error::Utf8)?),
+        Cow::Owned(bytes) => {
+            Cow::Owned(String::from_utf8(bytes).map_err(|e| quick_xml::Error::Utf8(e.utf8_error()))?)
+        }
+    })
+}
+
+// Reads the text of an element into `buf`, returning it still escaped.
+fn read_text<'a, B: BufRead>(reader: &mut Reader<B>, buf: &'a mut Vec<u8>) -> Option<&'a [u8]> {
+    buf.clear();
+    // the reader appends the text to the buffer, but it's borrowed from the event until then
+    let len = match reader.read_event(buf) {
+        Ok(Event::Text(e)) => e.escaped().len(),
+        _ => return None,
+    };
+    let buf: &'a Vec<u8> = buf;
+    Some(&buf[buf.len() - len..])
+}
+
+// Reads a revision's text up to </text>. It can come in several pieces, like when part of it is
+// in a CDATA section; text in one piece is borrowed from `buf`.
+fn read_content<'a, B: BufRead>(
+    reader: &mut Reader<B>,
+    buf: &'a mut Vec<u8>,
+    events: &mut Vec<u8>,
+    title: Option<&str>,
+) -> Result<Text<'a>> {
+    let mut text: quick_xml::Result<Cow<'a, str>> = Ok(Cow::Borrowed(""));
+    buf.clear();
+    let len = match reader.read_event(buf) {
+        Ok(Event::Text(e)) => Some(e.escaped().len()),
+        Ok(Event::CData(e)) => {
+            text = decode(&e).map(|piece| Cow::Owned(piece.into_owned()));
+            None
+        }
+        Ok(Event::End(ref e)) if e.name() == b"text" => return Ok(text.map(Some)),
+        Ok(Event::Eof) => return Err(xml_error(reader, title, quick_xml::Error::UnexpectedEof("text".to_owned()))),
+        Err(e) => return Err(xml_error(reader, title, e)),
+        _ => None,
+    };
+    if let Some(len) = len {
+        // the reader appended the text to the buffer
+        let buf: &'a Vec<u8> = buf;
+        text = decode(&buf[buf.len() - len..]);
+    }
     loop {
-        match reader.read_event(&mut buf) {
+        events.clear();
+        match reader.read_event(events) {
+            Ok(Event::Text(ref e)) | Ok(Event::CData(ref e)) => {
+                text = text.and_then(|text| {
+                    let mut text = text.into_owned();
+                    text.push_str(&decode(e)?);
+                    Ok(Cow::Owned(text))
+                });
+            }
+            Ok(Event::End(ref e)) if e.name() == b"text" => return Ok(text.map(Some)),
+            Ok(Event::Eof) => {
+                return Err(xml_error(reader, title, quick_xml::Error::UnexpectedEof("text".to_owned())))
+            }
+            Err(e) => return Err(xml_error(reader, title, e)),
+            _ => (),
+        }
+    }
+}
+
+/// Buffers that pages are read into, which can be reused from page to page.
+#[derive(Default)]
+pub struct PageBuffers {
+    events: Vec<u8>,
+    title: Vec<u8>,
+    text: Vec<u8>,
+}
+
+// A revision's text, if it has any, or why it couldn't be decoded.
+type Text<'a> = quick_xml::Result<Option<Cow<'a, str>>>;
+
+// Reads a revision's id and text. Errors decoding the text are returned in the inner result,
+// after reading up to </revision>.
+fn parse_revision<'a, B: BufRead>(
+    reader: &mut Reader<B>,
+    events: &mut Vec<u8>,
+    text_buf: &'a mut Vec<u8>,
+    title: Option<&str>,
+) -> Result<(Option<i64>, Text<'a>)> {
+    let mut id = None;
+    let mut result = Ok(None);
+    let mut text_buf = Some(text_buf);
+    loop {
+        events.clear();
+        match reader.read_event(events) {
+            // the contributor has an <id> too, but it comes after the revision's
+            Ok(Event::Start(ref e)) if e.name() == b"id" && id.is_none() => id = parse_id(reader, events),
             Ok(Event::Start(ref e)) if e.name() == b"text" => {
-                let mut buf = Vec::new();
-                match reader.read_event(&mut buf) {
-                    Ok(Event::Text(e)) => {
-                        let text = e.unescape_and_decode(&reader).unwrap();
-                        result = Some(text);
+                // text hidden by the admins is left out of dumps, as if the revision had none
+                let deleted = e.attributes().any(|a| a.is_ok_and(|a| a.key == b"deleted"));
+                if let Some(buf) = text_buf.take() {
+                    let text = read_content(reader, buf, events, title)?;
+                    if !deleted {
+                        result = text;
                     }
-                    _ => (),
                 }
             }
             Ok(Event::End(ref e)) if e.name() == b"revision" => break,
+            Ok(Event::Eof) => {
+                return Err(xml_error(reader, title, quick_xml::Error::UnexpectedEof("revision".to_owned())))
+            }
+            Err(e) => return Err(xml_error(reader, title, e)),
             _ => (),
         }
     }
-    result
+    Ok((id, result))
 }
 
-pub fn parse_page<B: BufRead>(mut reader: &mut Reader<B>) -> Option<Page> {
-    let mut buf = Vec::new();
-    let mut title = None;
+/// Reads a page up to its closing tag, into `bufs`. Returns `Ok(None)` for pages without a
+/// title, and `Error::MalformedPage` for pages whose title or text couldn't be decoded.
+pub fn parse_page<'a, B: BufRead>(reader: &mut Reader<B>, bufs: &'a mut PageBuffers) -> Result<Option<PageRef<'a>>> {
+    let PageBuffers {
+        events,
+        title: title_buf,
+        text: text_buf,
+    } = bufs;
+    let mut title_buf = Some(title_buf);
+    let mut text_buf = Some(text_buf);
+    let mut title: Option<Cow<'a, str>> = None;
+    let mut id = None;
+    let mut revision_id = None;
+    let mut redirect = None;
     let mut content = None;
+    let mut page_error = None;
+    let mut text_error = None;
     loop {
-        match reader.read_event(&mut buf) {
-            Ok(Event::Start(ref e)) => {
-                let mut buf = Vec::new();
-                match e.name() {
-                    b"title" => match reader.read_event(&mut buf) {
-                        Ok(Event::Text(e)) => {
-                            title = Some(e.unescape_and_decode(&reader).unwrap().clone())
+        events.clear();
+        match reader.read_event(events) {
+            Ok(Event::Start(ref e)) if e.name() == b"title" => {
+                if let Some(text) = title_buf.take().and_then(|buf| read_text(reader, buf)) {
+                    match decode(text) {
+                        Ok(text) => title = Some(text),
+                        Err(e) => page_error = Some(malformed_page(reader, title.as_deref(), e)),
+                    }
+                }
+            }
+            // revisions are read to their end below, so any <id> seen here is the page's
+            Ok(Event::Start(ref e)) if e.name() == b"id" => id = parse_id(reader, events),
+            Ok(Event::Empty(ref e)) | Ok(Event::Start(ref e)) if e.name() == b"redirect" => {
+                redirect = e
+                    .attributes()
+                    .filter_map(|a| a.ok())
+                    .find(|a| a.key == b"title")
+                    .and_then(|a| a.unescaped_value().ok().map(|value| String::from_utf8_lossy(&value).into_owned()));
+            }
+            // dumps of changes can have several revisions of a page, of which the newest is kept;
+            // those after the first are read into a buffer of their own
+            Ok(Event::Start(ref e)) if e.name() == b"revision" => {
+                let mut spare_buf = Vec::new();
+                let (id, text) = match text_buf.take() {
+                    Some(text_buf) => parse_revision(reader, events, text_buf, title.as_deref())?,
+                    None => {
+                        let (id, text) = parse_revision(reader, events, &mut spare_buf, title.as_deref())?;
+                        (id, text.map(|text| text.map(|text| Cow::Owned(text.into_owned()))))
+                    }
+                };
+                let newer = match (id, revision_id) {
+                    (Some(id), Some(newest)) => id >= newest,
+                    _ => true,
+                };
+                if newer {
+                    revision_id = id;
+                    match text {
+                        Ok(text) => {
+                            content = text;
+                            text_error = None;
                         }
-                        _ => (),
-                    },
-                    b"revision" => {
-                        content = parse_revision(&mut reader);
+                        Err(e) => text_error = Some(malformed_page(reader, title.as_deref(), e)),
                     }
-                    _ => (),
                 }
             }
             Ok(Event::End(ref e)) if e.name() == b"page" => break,
+            Ok(Event::Eof) => {
+                return Err(xml_error(reader, title.as_deref(), quick_xml::Error::UnexpectedEof("page".to_owned())))
+            }
+            Err(e) => return Err(xml_error(reader, title.as_deref(), e)),
             _ => (),
         }
     }
-    // and_then is a poor name for >>=
-    title.and_then(|title| content.map(|content| Page { title, content }))
+    if let Some(e) = page_error.or(text_error) {
+        return Err(e);
+    }
+    Ok(title.map(|title| PageRef {
+        title,
+        id,
+        revision_id,
+        redirect,
+        content: content.unwrap_or_default(),
+    }))
 }
 
-pub fn for_pages<F>(filename: &str, mut f: F)
+/// Calls `f` with every page in the dump at `filename`, reusing the same buffers for each page.
+/// Malformed pages are passed to `f` as errors; `f` can skip them by returning `Ok(())`, or stop
+/// reading by returning the error.
+pub fn for_pages<F>(filename: &str, mut f: F) -> Result<()>
 where
-    F: FnMut(Page) -> (),
+    F: FnMut(Result<PageRef>) -> Result<()>,
 {
+    for_pages_from(filename, 0, |page, _| f(page))
+}
+
+/// Like `for_pages`, but starts reading at byte `start`, which should be where a page ends, and
+/// also passes `f` the byte offset where each page ends, to start from later.
+pub fn for_pages_from<F>(filename: &str, start: u64, mut f: F) -> Result<()>
+where
+    F: FnMut(Result<PageRef>, u64) -> Result<()>,
+{
+    let path = Path::new(filename);
+    let mut file = File::open(path).map_err(|source| Error::Io {
+        path: path.to_owned(),
+        source,
+    })?;
+    file.seek(SeekFrom::Start(start)).map_err(|source| Error::Io {
+        path: path.to_owned(),
+        source,
+    })?;
     let mut buf = Vec::new();
-    let mut reader = Reader::from_file(Path::new(filename)).unwrap();
+    let mut bufs = PageBuffers::default();
+    let mut reader = Reader::from_reader(BufReader::new(file));
+    // starting partway through, we'll see the end of elements that started before
+    reader.check_end_names(start == 0);
     'read_words: loop {
         match reader.read_event(&mut buf) {
-            Ok(Event::Start(ref e)) => match e.name() {
-                b"page" => {
-                    let page = parse_page(&mut reader);
-                    page.map(|page| f(page));
+            Ok(Event::Start(ref e)) if e.name() == b"page" => {
+                let page = match parse_page(&mut reader, &mut bufs) {
+                    Ok(Some(page)) => Some(Ok(page)),
+                    Ok(None) => None,
+                    Err(e @ Error::MalformedPage { .. }) => Some(Err(e)),
+                    Err(e) => return Err(e),
+                };
+                if let Some(page) = page {
+                    f(page, start + reader.buffer_position() as u64)?;
                 }
-                _ => (),
-            },
+            }
             Ok(Event::Eof) => break 'read_words,
-            Err(e) => panic!("Error at position {}: {:?}", reader.buffer_position(), e),
+            Err(e) => return Err(xml_error(&reader, None, e)),
             _ => (),
         }
         buf.clear();
     }
+    Ok(())
 }
@@ -0,0 +1,261 @@
+//! Output formats for definitions: plain text, ANSI colors for terminals, Markdown and HTML.
+
+use colored::{Color, Colorize};
+
+use markup::{self, Span};
+
+/// Renders definitions, with their bold, italics and links, and the headings around them.
+/// `language` and `section` return whole lines, ending with a newline; the other methods return
+/// inline text without one.
+pub trait Renderer {
+    /// Renders text from a definition, escaping it if the format needs it.
+    fn text(&self, text: &str) -> String;
+
+    /// Renders a link to `target`, showing `text` (which isn't escaped yet).
+    fn link(&self, target: &str, anchor: Option<&str>, text: &str) -> String;
+
+    /// Makes already rendered text bold and/or italic.
+    fn style(&self, rendered: String, bold: bool, italic: bool) -> String;
+
+    /// Renders the heading for a language.
+    fn language(&self, language: &str) -> String;
+
+    /// Renders a part of speech or relation heading and the rendered definitions or terms under
+    /// it, numbered if `numbered` is set.
+    fn section(&self, heading: &str, items: &[String], numbered: bool) -> String;
+
+    /// Renders a definition's wikitext, which should have its templates expanded already.
+    fn inline(&self, wikitext: &str) -> String {
+        let mut result = String::new();
+        let (mut bold, mut italic) = (false, false);
+        for span in markup::spans(wikitext) {
+            let rendered = match span {
+                Span::Text(text) => self.text(text),
+                Span::Link { target, anchor, text } => self.link(target, anchor, text),
+                Span::Bold => {
+                    bold = !bold;
+                    continue;
+                }
+                Span::Italic => {
+                    italic = !italic;
+                    continue;
+                }
+            };
+            if bold || italic {
+                result.push_str(&self.style(rendered, bold, italic));
+            } else {
+                result.push_str(&rendered);
+            }
+        }
+        result
+    }
+}
+
+/// Escapes everything but unreserved characters, for putting a word in a URL.
+pub fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+pub fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The English Wiktionary page for a link, which is where Markdown and HTML links go by default.
+pub fn wiktionary_url(target: &str, anchor: Option<&str>) -> String {
+    let page = percent_encode(&target.replace(' ', "_"));
+    match anchor {
+        Some(anchor) => format!("https://en.wiktionary.org/wiki/{}#{}", page, percent_encode(&anchor.replace(' ', "_"))),
+        None => format!("https://en.wiktionary.org/wiki/{}", page),
+    }
+}
+
+// Word-wraps a list of items, indented under a heading.
+fn wrap_section(heading: String, items: &[String], numbered: bool, width: usize) -> String {
+    let mut result = format!("  {}\n", heading);
+    let digits = items.len().to_string().len();
+    for (i, item) in items.iter().enumerate() {
+        // numbered like a print dictionary, with wrapped lines lining up after the number
+        let (initial, subsequent) = if numbered {
+            let number = format!("    {:>digits$}. ", i + 1, digits = digits);
+            let indent = " ".repeat(number.len());
+            (number, indent)
+        } else {
+            ("    ".to_owned(), "      ".to_owned())
+        };
+        let opts = textwrap::Options::new(width)
+            .initial_indent(&initial)
+            .subsequent_indent(&subsequent);
+        result.push_str(&textwrap::fill(item, &opts));
+        result.push('\n');
+    }
+    result
+}
+
+/// Text without any markup, wrapped to `width` columns.
+pub struct Plain {
+    pub width: usize,
+}
+
+impl Renderer for Plain {
+    fn text(&self, text: &str) -> String {
+        text.to_owned()
+    }
+
+    fn link(&self, _target: &str, _anchor: Option<&str>, text: &str) -> String {
+        text.to_owned()
+    }
+
+    fn style(&self, rendered: String, _bold: bool, _italic: bool) -> String {
+        rendered
+    }
+
+    fn language(&self, language: &str) -> String {
+        format!("{}\n", language)
+    }
+
+    fn section(&self, heading: &str, items: &[String], numbered: bool) -> String {
+        wrap_section(heading.to_owned(), items, numbered, self.width)
+    }
+}
+
+/// Colored text for terminals, wrapped to `width` columns.
+pub struct Ansi {
+    pub width: usize,
+    pub language: Color,
+    pub heading: Color,
+    pub link: Color,
+}
+
+impl Renderer for Ansi {
+    fn text(&self, text: &str) -> String {
+        text.to_owned()
+    }
+
+    fn link(&self, _target: &str, _anchor: Option<&str>, text: &str) -> String {
+        text.color(self.link).to_string()
+    }
+
+    fn style(&self, rendered: String, bold: bool, italic: bool) -> String {
+        let mut styled = rendered.normal();
+        if bold {
+            styled = styled.bold();
+        }
+        if italic {
+            styled = styled.italic();
+        }
+        styled.to_string()
+    }
+
+    fn language(&self, language: &str) -> String {
+        format!("{}\n", language.color(self.language).bold())
+    }
+
+    fn section(&self, heading: &str, items: &[String], numbered: bool) -> String {
+        wrap_section(heading.color(self.heading).to_string(), items, numbered, self.width)
+    }
+}
+
+/// Markdown, with links going wherever `url` says.
+pub struct Markdown<F: Fn(&str, Option<&str>) -> String> {
+    pub url: F,
+}
+
+impl<F: Fn(&str, Option<&str>) -> String> Renderer for Markdown<F> {
+    fn text(&self, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            if "\\`*_[]<>#".contains(c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+
+    fn link(&self, target: &str, anchor: Option<&str>, text: &str) -> String {
+        format!("[{}]({})", self.text(text), (self.url)(target, anchor))
+    }
+
+    fn style(&self, rendered: String, bold: bool, italic: bool) -> String {
+        match (bold, italic) {
+            (true, true) => format!("***{}***", rendered),
+            (true, false) => format!("**{}**", rendered),
+            (false, true) => format!("*{}*", rendered),
+            (false, false) => rendered,
+        }
+    }
+
+    fn language(&self, language: &str) -> String {
+        format!("## {}\n\n", self.text(language))
+    }
+
+    fn section(&self, heading: &str, items: &[String], numbered: bool) -> String {
+        let mut result = format!("### {}\n\n", self.text(heading));
+        for (i, item) in items.iter().enumerate() {
+            if numbered {
+                result.push_str(&format!("{}. {}\n", i + 1, item));
+            } else {
+                result.push_str(&format!("- {}\n", item));
+            }
+        }
+        result.push('\n');
+        result
+    }
+}
+
+/// An HTML fragment, with links going wherever `url` says and each language heading's id set to
+/// the language, so links with anchors work.
+pub struct Html<F: Fn(&str, Option<&str>) -> String> {
+    pub url: F,
+}
+
+impl<F: Fn(&str, Option<&str>) -> String> Renderer for Html<F> {
+    fn text(&self, text: &str) -> String {
+        escape_html(text)
+    }
+
+    fn link(&self, target: &str, anchor: Option<&str>, text: &str) -> String {
+        format!("<a href=\"{}\">{}</a>", escape_html(&(self.url)(target, anchor)), escape_html(text))
+    }
+
+    fn style(&self, rendered: String, bold: bool, italic: bool) -> String {
+        let rendered = if italic { format!("<i>{}</i>", rendered) } else { rendered };
+        if bold {
+            format!("<b>{}</b>", rendered)
+        } else {
+            rendered
+        }
+    }
+
+    fn language(&self, language: &str) -> String {
+        format!("<h2 id=\"{0}\">{0}</h2>\n", escape_html(language))
+    }
+
+    fn section(&self, heading: &str, items: &[String], numbered: bool) -> String {
+        let list = if numbered { "ol" } else { "ul" };
+        let mut result = format!("<h3>{}</h3>\n<{}>\n", escape_html(heading), list);
+        for item in items {
+            result.push_str(&format!("<li>{}</li>\n", item));
+        }
+        result.push_str(&format!("</{}>\n", list));
+        result
+    }
+}
@@ -0,0 +1,247 @@
+//! Request handling for define3-server. This is kept apart from the HTTP library, so it can be
+//! tried against a database without running a server.
+
+use dictionary::{Dictionary, Filters};
+use error::{Error, Result};
+use markup;
+use render::{escape_html, percent_encode, Html, Renderer};
+use templates::Expander;
+
+// How many results /search returns without a `limit`, and at most.
+const SEARCH_LIMIT: u32 = 20;
+const MAX_SEARCH_LIMIT: u32 = 100;
+
+// A part of speech and its rendered definitions.
+type Section<'a> = (&'a str, Vec<String>);
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    /// Where to go instead, for redirects.
+    pub location: Option<String>,
+    pub body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: String) -> Response {
+        Response {
+            status,
+            content_type: "application/json; charset=utf-8",
+            location: None,
+            body,
+        }
+    }
+
+    fn html(status: u16, title: &str, body: &str) -> Response {
+        Response {
+            status,
+            content_type: "text/html; charset=utf-8",
+            location: None,
+            body: format!(
+                "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{}</title></head>\
+                 <body>{}</body></html>\n",
+                escape_html(title),
+                body,
+            ),
+        }
+    }
+
+    fn error(status: u16, message: &str, html: bool) -> Response {
+        if html {
+            Response::html(status, message, &format!("<p>{}</p>", escape_html(message)))
+        } else {
+            Response::json(status, json!({ "error": message }).to_string())
+        }
+    }
+
+    fn redirect(location: String) -> Response {
+        Response {
+            status: 302,
+            content_type: "text/plain; charset=utf-8",
+            location: Some(location),
+            body: String::new(),
+        }
+    }
+}
+
+/// Decodes `%XX` escapes, and `+` as a space if `plus_is_space`, as in query strings.
+pub fn percent_decode(s: &str, plus_is_space: bool) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = s.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', _) if plus_is_space => decoded.push(b' '),
+            (byte, _) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn define_url(word: &str, anchor: Option<&str>) -> String {
+    match anchor {
+        Some(anchor) => format!("/define/{}?format=html#{}", percent_encode(word), percent_encode(anchor)),
+        None => format!("/define/{}?format=html", percent_encode(word)),
+    }
+}
+
+fn define(
+    dict: &Dictionary,
+    expander: &Expander,
+    word: &str,
+    filters: &Filters,
+    related: bool,
+    html: bool,
+) -> Result<Response> {
+    let found = dict.lookup(word, filters)?;
+    let requested = word;
+    // redirects are followed, and the word is named after where they lead
+    let word = found.name.as_str();
+    let status = if found.meanings.is_empty() { 404 } else { 200 };
+    if !html {
+        let relations = if related { &found.relations[..] } else { &[] };
+        let json = ::json::word(word, &found.meanings, relations, |s| {
+            markup::strip_links(&expander.expand(dict, s))
+        });
+        return Ok(Response::json(status, json.to_string()));
+    }
+
+    // links like [[#English]] point into the word's own page
+    let renderer = Html {
+        url: |target: &str, anchor: Option<&str>| {
+            define_url(if target.is_empty() { word } else { target }, anchor)
+        },
+    };
+    let mut body = format!("<h1>{}</h1>\n", escape_html(word));
+    if word != requested {
+        body.push_str(&format!("<p>{} → {}</p>\n", escape_html(requested), escape_html(word)));
+    }
+    if found.meanings.is_empty() {
+        body.push_str(&format!("<p>No results found for {}.</p>\n", escape_html(word)));
+    }
+    // sections in the order they appear on the page
+    let mut sections: Vec<(&str, Vec<Section>)> = Vec::new();
+    for meaning in &found.meanings {
+        let definition = renderer.inline(&expander.expand(dict, &meaning.definition));
+        let language = meaning.language.as_str();
+        if sections.last().map(|&(last, _)| last) != Some(language) {
+            sections.push((language, Vec::new()));
+        }
+        let poses = &mut sections.last_mut().unwrap().1;
+        if poses.last().map(|&(last, _)| last) != Some(meaning.part_of_speech.as_str()) {
+            poses.push((&meaning.part_of_speech, Vec::new()));
+        }
+        poses.last_mut().unwrap().1.push(definition);
+    }
+    for (language, poses) in sections {
+        body.push_str(&renderer.language(language));
+        for (pos, definitions) in poses {
+            body.push_str(&renderer.section(pos, &definitions, true));
+        }
+    }
+    Ok(Response::html(status, word, &body))
+}
+
+fn search(dict: &Dictionary, query: &str, limit: u32, html: bool) -> Result<Response> {
+    let results = dict.search(query, limit)?;
+    if !html {
+        let json = json!({ "query": query, "results": results });
+        return Ok(Response::json(200, json.to_string()));
+    }
+    let items: String = results
+        .iter()
+        .map(|word| format!("<li><a href=\"{}\">{}</a></li>\n", escape_html(&define_url(word, None)), escape_html(word)))
+        .collect();
+    let title = format!("Search: {}", query);
+    Ok(Response::html(200, &title, &format!("<h1>{}</h1>\n<ul>\n{}</ul>\n", escape_html(&title), items)))
+}
+
+// The filters given by `lang` and `pos` parameters.
+fn filters(dict: &Dictionary, params: &[(String, String)]) -> Result<Filters> {
+    let mut filters = Filters::default();
+    for (key, value) in params {
+        match key.as_str() {
+            "lang" => filters.languages.push(dict.resolve_language(value)?),
+            "pos" => filters.parts_of_speech.extend(dict.resolve_part_of_speech(value)?),
+            "source" => filters.sources.push(dict.resolve_source(value)?),
+            _ => (),
+        }
+    }
+    Ok(filters)
+}
+
+/// Answers a request for `url` (a path and query string). Responses are JSON, unless the query
+/// has `format=html` or `accept` prefers HTML.
+///
+/// - `GET /define/WORD?lang=LANGUAGE&pos=POS&source=SOURCE&related` defines a word, optionally
+///   only in some languages (given by name, code or alias), parts of speech and sources, and with
+///   its relations to other words.
+/// - `GET /search?q=PATTERN&limit=N` lists headwords, like the REPL's completion.
+/// - `GET /random` redirects to a random word.
+pub fn handle(dict: &Dictionary, expander: &Expander, method: &str, url: &str, accept: Option<&str>) -> Response {
+    let (path, query) = match url.find('?') {
+        Some(i) => (&url[..i], &url[i + 1..]),
+        None => (url, ""),
+    };
+    let params: Vec<(String, String)> = query
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| match param.find('=') {
+            Some(i) => (percent_decode(&param[..i], true), percent_decode(&param[i + 1..], true)),
+            None => (percent_decode(param, true), String::new()),
+        })
+        .collect();
+    let param = |name: &str| {
+        params.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    };
+    let html = match param("format") {
+        Some(format) => format == "html",
+        None => accept.is_some_and(|accept| accept.contains("text/html")),
+    };
+
+    if method != "GET" && method != "HEAD" {
+        return Response::error(405, "Only GET requests are supported", html);
+    }
+
+    let result = if let Some(word) = path.strip_prefix("/define/") {
+        let filters = filters(dict, &params);
+        match filters {
+            Ok(filters) => {
+                let word = percent_decode(word, false);
+                define(dict, expander, &word, &filters, param("related").is_some(), html)
+            }
+            Err(e @ Error::UnknownLanguage(_))
+            | Err(e @ Error::UnknownPartOfSpeech(_))
+            | Err(e @ Error::UnknownSource(_)) => {
+                Ok(Response::error(400, &e.to_string(), html))
+            }
+            Err(e) => Err(e),
+        }
+    } else if path == "/search" {
+        let limit = param("limit")
+            .and_then(|limit| limit.parse().ok())
+            .unwrap_or(SEARCH_LIMIT)
+            .min(MAX_SEARCH_LIMIT);
+        match param("q") {
+            Some(query) if !query.is_empty() => search(dict, query, limit, html),
+            _ => Ok(Response::error(400, "Missing search query q", html)),
+        }
+    } else if path == "/random" {
+        dict.random_word().map(|word| match word {
+            Some(word) if html => Response::redirect(define_url(&word, None)),
+            Some(word) => Response::redirect(format!("/define/{}", percent_encode(&word))),
+            None => Response::error(404, "The dictionary is empty", html),
+        })
+    } else {
+        Ok(Response::error(404, "Not found", html))
+    };
+    result.unwrap_or_else(|e| Response::error(500, &e.to_string(), html))
+}
@@ -0,0 +1,75 @@
+//! Expansion of the wiki templates left in definitions, like `{{lb|en|slang}}`.
+
+use regex::{Captures, Regex};
+
+use dictionary::Dictionary;
+
+// TODO: Actually expand templates. This is very hard because Wikitext templates have a bunch of
+// functions and often call out into Lua code.
+// https://www.mediawiki.org/wiki/Help:Extension:ParserFunctions
+// https://www.mediawiki.org/wiki/Extension:Scribunto
+// For now, we just hardcode a couple common templates. Templates we don't know, or that are
+// missing arguments, are left as they are.
+fn replace_template(_dict: &Dictionary, caps: &Captures) -> String {
+    let s = caps.get(1).unwrap().as_str();
+    let elems: Vec<&str> = s.split('|').collect();
+    let arg = |i: usize| elems.get(i).cloned();
+    let replacement = match elems[0] {
+        "," =>
+            Some(",".to_owned()),
+        "ngd" | "unsupported" | "non-gloss definition" =>
+            arg(1).map(|a| a.to_owned()),
+        "alternative form of" =>
+            arg(1).map(|a| format!("Alternative form of [[{}]]", a)),
+        "ja-romanization of" =>
+            arg(1).map(|a| format!("Rōmaji transcription of [[{}]]", a)),
+        "sumti" =>
+            arg(1).map(|a| format!("x{}", a)),
+        "ja-def" =>
+            arg(1).map(|a| format!("{}:", a)),
+        "qualifier" =>
+            arg(1).map(|a| format!("({})", a)),
+        "lb" =>
+            arg(2).map(|a| format!("({})", a)),
+        "m" | "l" =>
+            arg(2).map(|a| format!("[[{}]]", a)),
+        _ => None,
+    };
+    replacement.unwrap_or_else(|| caps.get(0).unwrap().as_str().to_owned())
+}
+
+pub struct Expander {
+    re_template: Regex,
+}
+
+impl Expander {
+    pub fn new() -> Expander {
+        // TODO: We currently support nested templates in a very bad way. We expand templates in
+        // layers, most deeply nested first, and we do this by excluding curly braces in the regex.
+        // Should eventually use a more legit parser (nom maybe?)
+        Expander {
+            re_template: Regex::new(r"\{\{(?P<text>(?s:[^\{])*?)\}\}").unwrap(),
+        }
+    }
+
+    /// Expands the templates in a definition. Links made by templates are left as `[[links]]`.
+    pub fn expand(&self, dict: &Dictionary, s: &str) -> String {
+        let replace_template = |caps: &Captures| -> String { replace_template(dict, caps) };
+        let mut result = s.to_owned();
+        loop {
+            let result_ = self.re_template.replace_all(&result, &replace_template).to_string();
+            //println!("{}", result_);
+            if result == result_ {
+                break
+            }
+            result = result_;
+        }
+        result
+    }
+}
+
+impl Default for Expander {
+    fn default() -> Expander {
+        Expander::new()
+    }
+}
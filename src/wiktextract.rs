@@ -0,0 +1,116 @@
+//! Conversion of words to the JSON format used by wiktextract
+//! (https://github.com/tatuylonen/wiktextract), so define3's data can be fed to tools built
+//! around it.
+
+use serde_json::{Map, Value};
+
+use markup::{self, strip_links, Span};
+use Word;
+
+/// Maps a part of speech heading to wiktextract's `pos` code, e.g. "Proper noun" to "name".
+pub fn pos_code(part_of_speech: &str) -> String {
+    match part_of_speech {
+        "Noun" => "noun",
+        "Proper noun" => "name",
+        "Verb" => "verb",
+        "Adjective" => "adj",
+        "Adverb" => "adv",
+        "Pronoun" => "pron",
+        "Preposition" => "prep",
+        "Postposition" => "postp",
+        "Conjunction" => "conj",
+        "Interjection" => "intj",
+        "Determiner" => "det",
+        "Article" => "article",
+        "Numeral" | "Number" => "num",
+        "Particle" => "particle",
+        "Prefix" => "prefix",
+        "Suffix" => "suffix",
+        "Infix" => "infix",
+        "Phrase" => "phrase",
+        "Proverb" => "proverb",
+        "Initialism" | "Acronym" | "Abbreviation" => "abbrev",
+        "Romanization" => "romanization",
+        "Han character" | "Hanzi" | "Hanja" | "Kanji" => "character",
+        _ => return part_of_speech.to_lowercase(),
+    }
+    .to_owned()
+}
+
+/// Maps a relation as stored in the database to the wiktextract field listing it.
+pub fn relation_field(relation: &str) -> String {
+    match relation {
+        "synonym" => "synonyms",
+        "antonym" => "antonyms",
+        "hypernym" => "hypernyms",
+        "hyponym" => "hyponyms",
+        "meronym" => "meronyms",
+        "holonym" => "holonyms",
+        "troponym" => "troponyms",
+        "coordinate" => "coordinate_terms",
+        "derived" => "derived",
+        "related" => "related",
+        _ => return relation.to_owned(),
+    }
+    .to_owned()
+}
+
+/// Converts a word to wiktextract entries, one per language and part of speech, in the order
+/// they first appear in `word.meanings`. Relations aren't tied to a part of speech in define3, so
+/// they go on the first entry for their language.
+pub fn entries(word: &Word) -> Vec<Value> {
+    let mut keys: Vec<(&str, &str)> = Vec::new();
+    for meaning in &word.meanings {
+        let key = (meaning.language.as_str(), meaning.part_of_speech.as_str());
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut languages_with_relations: Vec<&str> = Vec::new();
+    for (language, part_of_speech) in keys {
+        let senses: Vec<Value> = word
+            .meanings
+            .iter()
+            .filter(|m| m.language == language && m.part_of_speech == part_of_speech)
+            .map(|m| {
+                let mut sense = json!({ "glosses": [strip_links(&m.definition)] });
+                let links: Vec<Value> = markup::spans(&m.definition)
+                    .into_iter()
+                    .filter_map(|span| match span {
+                        Span::Link { target, text, .. } => Some(json!([text, target])),
+                        _ => None,
+                    })
+                    .collect();
+                if !links.is_empty() {
+                    sense["links"] = json!(links);
+                }
+                sense
+            })
+            .collect();
+        let mut entry = Map::new();
+        entry.insert("word".to_owned(), json!(word.name));
+        entry.insert("lang".to_owned(), json!(language));
+        entry.insert("pos".to_owned(), json!(pos_code(part_of_speech)));
+        entry.insert("senses".to_owned(), json!(senses));
+        if !languages_with_relations.contains(&language) {
+            languages_with_relations.push(language);
+            for relation in word.relations.iter().filter(|r| r.language == language) {
+                let mut item = Map::new();
+                item.insert("word".to_owned(), json!(relation.target));
+                if let Some(ref sense) = relation.sense {
+                    item.insert("sense".to_owned(), json!(strip_links(sense)));
+                }
+                let field = entry
+                    .entry(relation_field(&relation.relation))
+                    .or_insert_with(|| json!([]));
+                if let Value::Array(ref mut items) = *field {
+                    items.push(Value::Object(item));
+                }
+            }
+        }
+        result.push(Value::Object(entry));
+    }
+    result
+}
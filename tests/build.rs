@@ -0,0 +1,209 @@
+extern crate define3;
+extern crate rusqlite;
+
+use define3::dictionary::{create_indexes, create_tables, tune_for_building, Batch};
+use define3::{Dictionary, Filters};
+
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+const PAGES: usize = 200_000;
+
+// A table, its columns and the values of a row for them.
+type Row = (&'static str, &'static [&'static str], Vec<Value>);
+
+// Writes a dump of `pages` word pages, each with a few definitions, links and synonyms.
+fn synthetic_dump(path: &PathBuf, pages: usize) {
+    let mut out = BufWriter::new(File::create(path).unwrap());
+    writeln!(out, "<mediawiki>").unwrap();
+    for i in 0..pages {
+        writeln!(
+            out,
+            "<page><title>word{0}</title><id>{0}</id><revision><id>{1}</id>\
+             <text xml:space=\"preserve\">==English==\n\n===Noun===\n\
+             # A [[word{2}|kind]] of ''thing'' number {0}.\n\
+             # Something like [[word{3}]].\n\n\
+             ====Synonyms====\n* [[word{2}]]\n\n\
+             ===Verb===\n# To do {0}.\n\n\
+             ==French==\n\n===Noun===\n# {{{{l|en|word{0}}}}}\n\
+             </text></revision></page>",
+            i,
+            i + 1_000_000,
+            (i + 1) % pages,
+            (i + 7) % pages
+        )
+        .unwrap();
+    }
+    writeln!(out, "</mediawiki>").unwrap();
+}
+
+// Run with `cargo test --release --test build -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn benchmark_build() {
+    let mut dir = env::temp_dir();
+    dir.push(format!("define3-bench-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let dump = dir.join("synthetic-20000101-pages-meta-current.xml");
+    synthetic_dump(&dump, PAGES);
+
+    let started = Instant::now();
+    let status = Command::new(env!("CARGO_BIN_EXE_build_definitions_db"))
+        .arg(&dump)
+        .arg("--quiet")
+        .env("XDG_DATA_HOME", &dir)
+        .status()
+        .unwrap();
+    let elapsed = started.elapsed();
+    assert!(status.success());
+    println!(
+        "built {} pages in {:.2}s ({:.0} pages/s)",
+        PAGES,
+        elapsed.as_secs_f64(),
+        PAGES as f64 / elapsed.as_secs_f64()
+    );
+
+    let dict = Dictionary::open(dir.join("define3").join("define3.sqlite3")).unwrap();
+    let word = dict.lookup("word42", &Filters::default()).unwrap();
+    assert_eq!(word.meanings.len(), 4);
+    assert_eq!(word.relations.len(), 1);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// The rows the builder saves from the synthetic dump's `pages` pages, by table and columns.
+fn synthetic_rows(pages: usize) -> Vec<Row> {
+    let mut rows: Vec<Row> = Vec::new();
+    for i in 0..pages {
+        let entry = i as i64 + 1;
+        let text = |text: String| Value::Text(text);
+        rows.push((
+            "entries",
+            &["id", "source_id", "name", "page_id", "revision_id"],
+            vec![entry.into(), 1.into(), text(format!("word{}", i)), entry.into(), (entry + 1_000_000).into()],
+        ));
+        for (language, pos, definition) in [
+            (1, 1, format!("A [[word{}|kind]] of ''thing'' number {}.", (i + 1) % pages, i)),
+            (1, 1, format!("Something like [[word{}]].", (i + 7) % pages)),
+            (1, 2, format!("To do {}.", i)),
+            (2, 1, format!("{{{{l|en|word{}}}}}", i)),
+        ] {
+            rows.push((
+                "senses",
+                &["entry_id", "language_id", "pos_id", "definition"],
+                vec![entry.into(), language.into(), pos.into(), text(definition)],
+            ));
+        }
+        let (first, second) = (format!("word{}", (i + 1) % pages), format!("word{}", (i + 7) % pages));
+        for (target, link_text) in [(first, "kind".to_owned()), (second.clone(), second)] {
+            rows.push((
+                "links",
+                &["entry_id", "language_id", "target", "anchor", "text"],
+                vec![entry.into(), 1.into(), text(target), Value::Null, text(link_text)],
+            ));
+        }
+        rows.push((
+            "relations",
+            &["entry_id", "language_id", "relation", "target", "sense"],
+            vec![entry.into(), 1.into(), text("synonym".to_owned()), text(format!("word{}", (i + 1) % pages)), Value::Null],
+        ));
+    }
+    rows
+}
+
+// Creates a database with a source, two languages and two parts of speech to save rows under.
+fn benchmark_database(path: &PathBuf) -> Connection {
+    let _ = fs::remove_file(path);
+    let conn = Connection::open(path).unwrap();
+    create_tables(&conn).unwrap();
+    conn.execute_batch(
+        "INSERT INTO sources VALUES (1, 'synthetic', 'en', '20000101', '');
+         INSERT INTO languages VALUES (1, 'English', 'en', NULL, NULL);
+         INSERT INTO languages VALUES (2, 'French', 'fr', NULL, NULL);
+         INSERT INTO pos VALUES (1, 'Noun');
+         INSERT INTO pos VALUES (2, 'Verb');",
+    )
+    .unwrap();
+    conn
+}
+
+// Saves rows the way the builder used to: each with its own statement, parsed again every time,
+// into a journaled database that keeps its indexes up to date.
+fn save_unbatched(conn: &mut Connection, rows: &[Row]) -> Duration {
+    let started = Instant::now();
+    create_indexes(conn).unwrap();
+    let tx = conn.transaction().unwrap();
+    for (table, columns, values) in rows {
+        let placeholders: Vec<String> = (1..=values.len()).map(|i| format!("?{}", i)).collect();
+        tx.execute(
+            &format!("insert into {} ({}) values ({})", table, columns.join(", "), placeholders.join(", ")),
+            params_from_iter(values),
+        )
+        .unwrap();
+    }
+    tx.commit().unwrap();
+    started.elapsed()
+}
+
+// Saves rows the way the builder does now: in the builder's batches, into a database tuned like
+// the builder's, indexed at the end.
+fn save_batched(conn: &mut Connection, rows: &[Row]) -> Duration {
+    let started = Instant::now();
+    tune_for_building(conn).unwrap();
+    let tx = conn.transaction().unwrap();
+    let mut batches: Vec<(&str, Batch)> = Vec::new();
+    for &(table, columns, ref values) in rows {
+        // entries are inserted one at a time, since their ids are needed straight away
+        if table == "entries" {
+            tx.prepare_cached("insert into entries (id, source_id, name, page_id, revision_id) values (?, ?, ?, ?, ?)")
+                .unwrap()
+                .execute(params_from_iter(values))
+                .unwrap();
+            continue;
+        }
+        let i = match batches.iter().position(|&(name, _)| name == table) {
+            Some(i) => i,
+            None => {
+                batches.push((table, Batch::new(table, columns, "benchmarking")));
+                batches.len() - 1
+            }
+        };
+        batches[i].1.push(&tx, values.clone()).unwrap();
+    }
+    for (_, batch) in &mut batches {
+        batch.flush(&tx).unwrap();
+    }
+    tx.commit().unwrap();
+    create_indexes(conn).unwrap();
+    started.elapsed()
+}
+
+// Saves the synthetic dump's rows with and without the builder's `Batch`, and checks that batching
+// is faster. Run like benchmark_build.
+#[test]
+#[ignore]
+fn benchmark_batching() {
+    let rows = synthetic_rows(PAGES);
+    let mut path = env::temp_dir();
+    path.push(format!("define3-bench-batching-{}.sqlite3", std::process::id()));
+
+    let unbatched = save_unbatched(&mut benchmark_database(&path), &rows);
+    let batched = save_batched(&mut benchmark_database(&path), &rows);
+    let _ = fs::remove_file(&path);
+
+    let speedup = unbatched.as_secs_f64() / batched.as_secs_f64();
+    println!(
+        "saved {} rows in {:.2}s unbatched and {:.2}s batched ({:.1}x faster)",
+        rows.len(),
+        unbatched.as_secs_f64(),
+        batched.as_secs_f64(),
+        speedup
+    );
+    assert!(speedup > 1.0);
+}
@@ -0,0 +1,205 @@
+extern crate define3;
+extern crate rusqlite;
+
+use define3::{Dictionary, Filters};
+
+use rusqlite::Connection;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn fixture(name: &str) -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("tests");
+    path.push("fixtures");
+    path.push(name);
+    path
+}
+
+// An empty directory to keep a test's database in, as its XDG_DATA_HOME.
+fn data_dir(name: &str) -> PathBuf {
+    let mut dir = env::temp_dir();
+    dir.push(format!("define3-test-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Runs build_definitions_db on the database in `dir`.
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_build_definitions_db"))
+        .args(args)
+        .arg("--quiet")
+        .env("XDG_DATA_HOME", dir)
+        .output()
+        .unwrap()
+}
+
+fn database(dir: &Path) -> PathBuf {
+    dir.join("define3").join("define3.sqlite3")
+}
+
+#[test]
+fn appends_editions_with_their_modules() {
+    let dir = data_dir("appends_editions");
+    let en = fixture("enwiktionary-20240101-pages-meta-current.xml");
+    let fr = fixture("frwiktionary-20240301-pages-meta-current.xml");
+    assert!(run(&dir, &[en.to_str().unwrap()]).status.success());
+    assert!(run(&dir, &[fr.to_str().unwrap(), "--edition", "fr", "--append"]).status.success());
+
+    let conn = Connection::open(database(&dir)).unwrap();
+    let mut stmt = conn
+        .prepare(
+            "SELECT sources.name, modules.name FROM modules JOIN sources ON sources.id = modules.source_id
+             ORDER BY modules.rowid",
+        )
+        .unwrap();
+    let modules: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap();
+    assert_eq!(
+        modules,
+        [
+            ("enwikt".to_owned(), "languages/data/2".to_owned()),
+            ("enwikt".to_owned(), "languages/data/3/g".to_owned()),
+            ("frwikt".to_owned(), "langues/données".to_owned()),
+        ]
+    );
+    // modules are only kept in the database
+    let files: Vec<_> = fs::read_dir(dir.join("define3")).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+    assert_eq!(files, ["define3.sqlite3"]);
+
+    let dict = Dictionary::open(database(&dir)).unwrap();
+    let chat = dict.lookup("chat", &Filters::default()).unwrap();
+    let sources: Vec<&str> = chat.meanings.iter().map(|meaning| meaning.source.as_str()).collect();
+    assert_eq!(sources, ["enwikt", "enwikt", "frwikt"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// Writes a dump of `pages` words to `path`, with the page at `malformed` being unreadable.
+fn numbered_dump(path: &Path, pages: usize, malformed: Option<usize>) {
+    let mut out = fs::File::create(path).unwrap();
+    writeln!(out, "<mediawiki>").unwrap();
+    for i in 0..pages {
+        let text = if Some(i) == malformed { "&bogus;" } else { "ok" };
+        writeln!(
+            out,
+            "<page><title>word{0}</title><id>{1}</id><revision><id>{2}</id>\
+             <text>==English==\n===Noun===\n# Number {0}, {3}.</text></revision></page>",
+            i,
+            i + 1,
+            i + 100_000,
+            text
+        )
+        .unwrap();
+    }
+    writeln!(out, "</mediawiki>").unwrap();
+}
+
+#[test]
+fn resumes_interrupted_builds() {
+    let dir = data_dir("resumes");
+    let dump = dir.join("numbered-20240101-pages-meta-current.xml");
+    let dump_arg = dump.to_str().unwrap();
+    // checkpoints come every 10000 pages, so the build stops after one
+    numbered_dump(&dump, 10_005, Some(10_002));
+    let output = run(&dir, &[dump_arg, "--strict"]);
+    assert!(!output.status.success());
+    let temp = dir.join("define3").join("define3.sqlite3.tmp");
+    let checkpoint: Vec<(String, String)> = {
+        let conn = Connection::open(&temp).unwrap();
+        let mut stmt = conn
+            .prepare("SELECT key, value FROM meta WHERE key IN ('checkpoint_pass', 'checkpoint_skipped') ORDER BY key")
+            .unwrap();
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+        rows.collect::<rusqlite::Result<_>>().unwrap()
+    };
+    assert_eq!(
+        checkpoint,
+        [("checkpoint_pass".to_owned(), "1".to_owned()), ("checkpoint_skipped".to_owned(), "0".to_owned())]
+    );
+
+    // another dump can't carry on the build
+    let other = dir.join("other-20240101-pages-meta-current.xml");
+    numbered_dump(&other, 10_005, None);
+    let output = run(&dir, &[other.to_str().unwrap(), "--resume"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("the interrupted build was reading"), "{}", stderr);
+    assert!(stderr.contains("numbered-20240101"), "{}", stderr);
+
+    // the fixed dump can
+    numbered_dump(&dump, 10_005, None);
+    let output = Command::new(env!("CARGO_BIN_EXE_build_definitions_db"))
+        .args([dump_arg, "--resume"])
+        .env("XDG_DATA_HOME", &dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let offset: u64 = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("Resuming pass 1 at byte "))
+        .unwrap_or_else(|| panic!("didn't resume: {}", stdout))
+        .parse()
+        .unwrap();
+    assert!(offset > 0);
+    assert!(!temp.exists());
+
+    let dict = Dictionary::open(database(&dir)).unwrap();
+    for word in &["word0", "word10002", "word10004"] {
+        assert_eq!(dict.lookup(word, &Filters::default()).unwrap().meanings.len(), 1, "{}", word);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn applies_updates() {
+    let dir = data_dir("updates");
+    let dump = fixture("enwiktionary-20240101-pages-meta-current.xml");
+    let changes = fixture("enwiktionary-20240102-pages-meta-hist-incr.xml");
+    assert!(run(&dir, &[dump.to_str().unwrap()]).status.success());
+    let output = Command::new(env!("CARGO_BIN_EXE_build_definitions_db"))
+        .args(["--update", changes.to_str().unwrap()])
+        .env("XDG_DATA_HOME", &dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Updated 2 pages, deleted 1 and left 1 unchanged"), "{}", stdout);
+
+    let dict = Dictionary::open(database(&dir)).unwrap();
+    let definitions = |word| -> Vec<String> {
+        let word = dict.lookup(word, &Filters::default()).unwrap();
+        word.meanings.into_iter().map(|meaning| meaning.definition).collect()
+    };
+    // the newest of cat's revisions replaces it, under its page id rather than a contributor's
+    assert_eq!(definitions("cat"), ["A changed definition."]);
+    // chat was emptied
+    assert!(definitions("chat").is_empty());
+    assert_eq!(definitions("dog"), ["A [[canine]]."]);
+    // the language data was already at revision 12
+    assert_eq!(dict.resolve_language("en").unwrap(), "English");
+
+    let conn = Connection::open(database(&dir)).unwrap();
+    let page_ids: Vec<(String, i64, i64)> = {
+        let mut stmt = conn.prepare("SELECT name, page_id, revision_id FROM entries ORDER BY name").unwrap();
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))).unwrap();
+        rows.collect::<rusqlite::Result<_>>().unwrap()
+    };
+    assert_eq!(page_ids, [("cat".to_owned(), 1, 16), ("dog".to_owned(), 7, 21)]);
+
+    // applying the same changes again leaves everything as it is
+    let output = Command::new(env!("CARGO_BIN_EXE_build_definitions_db"))
+        .args(["--update", changes.to_str().unwrap()])
+        .env("XDG_DATA_HOME", &dir)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Updated 0 pages, deleted 0 and left 4 unchanged"), "{}", stdout);
+    fs::remove_dir_all(&dir).unwrap();
+}
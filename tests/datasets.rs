@@ -0,0 +1,129 @@
+extern crate define3;
+
+use define3::datasets::{cedict, jmdict, wordnet};
+use define3::{Error, Word};
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("tests");
+    path.push("fixtures");
+    path.push(name);
+    path
+}
+
+fn find<'a>(words: &'a [Word], name: &str) -> &'a Word {
+    words.iter().find(|word| word.name == name).unwrap_or_else(|| panic!("no word {}", name))
+}
+
+// The part of speech and definition of each of a word's meanings.
+fn meanings(word: &Word) -> Vec<(&str, &str)> {
+    word.meanings
+        .iter()
+        .map(|meaning| (meaning.part_of_speech.as_str(), meaning.definition.as_str()))
+        .collect()
+}
+
+// The relation and target of each of a word's relations.
+fn relations(word: &Word) -> Vec<(&str, &str)> {
+    word.relations
+        .iter()
+        .map(|relation| (relation.relation.as_str(), relation.target.as_str()))
+        .collect()
+}
+
+#[test]
+fn reads_jmdict() {
+    let words = jmdict::read(fixture("JMdict_e.xml")).unwrap();
+    let names: Vec<&str> = words.iter().map(|word| word.name.as_str()).collect();
+    assert_eq!(names, ["猫", "ねこ", "勉強", "べんきょう"]);
+
+    let neko = find(&words, "猫");
+    assert_eq!(
+        meanings(neko),
+        [("Noun", "cat (esp. the domestic cat, Felis catus)"), ("Noun", "shamisen")]
+    );
+    assert!(neko.meanings.iter().all(|m| m.language == "Japanese" && m.source == "jmdict"));
+    // entries with the same reading are meanings of the same word
+    assert_eq!(find(&words, "ねこ").meanings.len(), 3);
+    assert_eq!(find(&words, "ねこ").meanings[2].part_of_speech, "Verb");
+
+    assert_eq!(
+        meanings(find(&words, "べんきょう")),
+        [("Noun", "study; learning & practice"), ("Verb", "study; learning & practice")]
+    );
+}
+
+#[test]
+fn reads_cedict() {
+    let words = cedict::read(fixture("cedict_ts.u8")).unwrap();
+    let names: Vec<&str> = words.iter().map(|word| word.name.as_str()).collect();
+    assert_eq!(names, ["貓", "猫", "中國", "中国", "綠色", "绿色", "好"]);
+
+    assert_eq!(meanings(find(&words, "猫")), [("Unclassified", "(māo) cat; CL:隻|只[zhi1]")]);
+    assert_eq!(find(&words, "中国").meanings[0].definition, "(Zhōng guó) China");
+    assert_eq!(find(&words, "绿色").meanings[0].definition, "(lǜ sè) green");
+    assert_eq!(
+        meanings(find(&words, "好")),
+        [("Unclassified", "(hǎo) good; well"), ("Unclassified", "(hào) to be fond of")]
+    );
+    assert_eq!(find(&words, "好").meanings[0].language, "Chinese");
+    assert_eq!(find(&words, "好").meanings[0].source, "cedict");
+}
+
+#[test]
+fn converts_pinyin() {
+    assert_eq!(cedict::pinyin("xiao3 gou3"), "xiǎo gǒu");
+    assert_eq!(cedict::pinyin("duo1 shao5"), "duō shao");
+    assert_eq!(cedict::pinyin("Lu:3 gui4"), "Lǚ guì");
+    assert_eq!(cedict::pinyin("A A zhi4"), "A A zhì");
+}
+
+#[test]
+fn refuses_malformed_cedict() {
+    let mut path = env::temp_dir();
+    path.push(format!("define3-test-cedict-{}.u8", std::process::id()));
+    fs::write(&path, "# comment\n貓 猫 [mao1] /cat/\n貓 猫 mao1 cat\n").unwrap();
+    let result = cedict::read(&path);
+    let _ = fs::remove_file(&path);
+    match result {
+        Err(Error::MalformedLine { line, .. }) => assert_eq!(line, 3),
+        other => panic!("expected a malformed line, got {:?}", other),
+    }
+}
+
+#[test]
+fn reads_wordnet() {
+    let words = wordnet::read(fixture("wordnet")).unwrap();
+
+    let cat = find(&words, "cat");
+    assert_eq!(meanings(cat)[0].0, "Noun");
+    assert!(meanings(cat)[0].1.starts_with("feline mammal usually having thick soft fur"));
+    assert_eq!(meanings(cat)[1], ("Verb", "beat with a cat-o'-nine-tails"));
+    assert!(cat.meanings.iter().all(|m| m.language == "English" && m.source == "wordnet"));
+    assert_eq!(
+        relations(cat),
+        [
+            ("synonym", "true cat"),
+            ("synonym", "kitty"),
+            ("hypernym", "feline"),
+            ("hyponym", "house cat"),
+        ]
+    );
+    assert_eq!(
+        cat.relations[0].sense.as_deref(),
+        Some("feline mammal usually having thick soft fur and no ability to roar: domestic cats")
+    );
+    // lexical pointers only relate the words they're from
+    assert_eq!(
+        relations(find(&words, "kitty")),
+        [("synonym", "cat"), ("synonym", "true cat"), ("hypernym", "feline"), ("hyponym", "house cat")]
+    );
+
+    assert_eq!(relations(find(&words, "able")), [("antonym", "unable")]);
+    assert_eq!(meanings(find(&words, "galore")), [("Adjective", "in great numbers; \"cats galore\"")]);
+    assert_eq!(meanings(find(&words, "ably")), [("Adverb", "with competence")]);
+}
@@ -0,0 +1,128 @@
+extern crate define3;
+
+use define3::languages::{is_data_module, parse_module, Language};
+use define3::{Dictionary, Error};
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn language(code: &str, name: &str, family: Option<&str>, scripts: &[&str], aliases: &[&str]) -> Language {
+    Language {
+        code: code.to_owned(),
+        canonical_name: name.to_owned(),
+        family: family.map(str::to_owned),
+        scripts: scripts.iter().map(|&s| s.to_owned()).collect(),
+        aliases: aliases.iter().map(|&s| s.to_owned()).collect(),
+    }
+}
+
+#[test]
+fn positional_fields() {
+    let languages = parse_module(
+        "local m = {}\n\
+         m[\"gsw\"] = {\n\
+             \"Alemannic German\",\n\
+             131339,\n\
+             \"gmw-hgm\",\n\
+             \"Latn, Grek\",\n\
+             otherNames = {\"Alsatian\", \"Swiss German\"},\n\
+             ancestors = {\"gmh\"},\n\
+             translit = function(text) return text end,\n\
+         }\n\
+         return m",
+    );
+    assert_eq!(
+        languages,
+        [language(
+            "gsw",
+            "Alemannic German",
+            Some("gmw-hgm"),
+            &["Latn", "Grek"],
+            &["Alsatian", "Swiss German"]
+        )]
+    );
+}
+
+#[test]
+fn named_fields() {
+    let languages = parse_module(
+        "m[\"en\"] = {canonicalName = 'English', family = \"gmw\", scripts = {\"Latn\", \"Brai\"}}\n\
+         m['fr'] = {canonicalName = \"French\", aliases = {\"Modern French\"}, otherNames = {\"Français\"}}",
+    );
+    assert_eq!(
+        languages,
+        [
+            language("en", "English", Some("gmw"), &["Latn", "Brai"], &[]),
+            language("fr", "French", None, &[], &["Français", "Modern French"]),
+        ]
+    );
+}
+
+#[test]
+fn long_bracket_strings() {
+    let languages = parse_module("m[\"x\"] = {[[Long \"quoted\" name]], 1, [==[fam]]ily]==]}");
+    assert_eq!(languages, [language("x", "Long \"quoted\" name", Some("fam]]ily"), &[], &[])]);
+}
+
+#[test]
+fn comments() {
+    let languages = parse_module(
+        "--[[ m[\"no\"] = {\"Commented out\"} ]]\n\
+         --[==[ m[\"nope\"] = {\"Also commented out\"} ]==]\n\
+         m[\"en\"] = { -- the English language\n\
+             \"English\", -- m[\"x\"] = {\"Not a language\"}\n\
+         }",
+    );
+    assert_eq!(languages, [language("en", "English", None, &[], &[])]);
+}
+
+#[test]
+fn comments_at_the_end() {
+    assert_eq!(parse_module("m[\"en\"] = {\"English\"}\n--").len(), 1);
+    assert_eq!(parse_module("m[\"en\"] = {\"English\"}\n--é").len(), 1);
+    assert_eq!(parse_module("m[\"en\"] = {\"English\"} --[[ unclosed").len(), 1);
+}
+
+#[test]
+fn data_modules() {
+    assert!(is_data_module("languages/data/2"));
+    assert!(is_data_module("languages/data/3/g"));
+    assert!(!is_data_module("languages/data/2/documentation"));
+    assert!(!is_data_module("languages/utilities"));
+}
+
+// Builds a database from the dump in tests/fixtures, whose language data modules have English,
+// French and Alemannic German.
+fn build(name: &str) -> PathBuf {
+    let mut dir = env::temp_dir();
+    dir.push(format!("define3-test-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let mut dump = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    dump.push("tests/fixtures/enwiktionary-20240101-pages-meta-current.xml");
+    let status = Command::new(env!("CARGO_BIN_EXE_build_definitions_db"))
+        .arg(&dump)
+        .arg("--quiet")
+        .env("XDG_DATA_HOME", &dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    dir
+}
+
+#[test]
+fn resolves_languages() {
+    let dir = build("resolves_languages");
+    let dict = Dictionary::open(dir.join("define3").join("define3.sqlite3")).unwrap();
+    assert_eq!(dict.resolve_language("english").unwrap(), "English");
+    assert_eq!(dict.resolve_language("FR").unwrap(), "French");
+    assert_eq!(dict.resolve_language("gsw").unwrap(), "Alemannic German");
+    assert_eq!(dict.resolve_language("Modern French").unwrap(), "French");
+    assert_eq!(dict.resolve_language("swiss german").unwrap(), "Alemannic German");
+    match dict.resolve_language("Klingon") {
+        Err(Error::UnknownLanguage(language)) => assert_eq!(language, "Klingon"),
+        other => panic!("expected an unknown language, got {:?}", other),
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
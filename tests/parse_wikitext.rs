@@ -0,0 +1,229 @@
+extern crate define3;
+
+use define3::editions::{Edition, ENGLISH, FRENCH, GERMAN, JAPANESE, RUSSIAN};
+use define3::parse_wikitext::parse_wikitext;
+
+type Triple = (String, String, String);
+
+// The (language, part of speech, definition) of each meaning and the (language, relation, target)
+// of each relation on a page.
+fn parse(text: &str, edition: &Edition) -> (Vec<Triple>, Vec<Triple>) {
+    let (meanings, relations) = parse_wikitext(text, edition);
+    (
+        meanings
+            .into_iter()
+            .map(|m| (m.language, m.part_of_speech, m.definition))
+            .collect(),
+        relations
+            .into_iter()
+            .map(|r| (r.language, r.relation, r.target))
+            .collect(),
+    )
+}
+
+fn triple(a: &str, b: &str, c: &str) -> Triple {
+    (a.to_owned(), b.to_owned(), c.to_owned())
+}
+
+#[test]
+fn english() {
+    let (meanings, relations) = parse(
+        "==English==\n===Noun===\n# A [[feline]].\n#: {{syn|en|kitty}}\n\n\
+         ====Synonyms====\n* [[puss]]\n\n===Verb===\n# To vomit.\n\n\
+         ==Klingon==\n===Noun===\n# Not saved.\n",
+        &ENGLISH,
+    );
+    assert_eq!(
+        meanings,
+        vec![
+            triple("English", "Noun", "A [[feline]]."),
+            triple("English", "Verb", "To vomit."),
+        ]
+    );
+    assert_eq!(
+        relations,
+        vec![
+            triple("English", "synonym", "kitty"),
+            triple("English", "synonym", "puss"),
+        ]
+    );
+}
+
+#[test]
+fn french() {
+    let (meanings, relations) = parse(
+        "== {{langue|fr}} ==\n=== {{S|étymologie}} ===\n: Du latin.\n\n\
+         === {{S|nom|fr}} ===\n'''chat''' {{pron|ʃa|fr}} {{m}}\n\
+         # ''(Zoologie)'' [[mammifère|Mammifère]] carnivore.\n#* ''Un exemple.''\n\n\
+         ==== {{S|synonymes}} ====\n* [[matou]]\n\n\
+         == {{langue|en}} ==\n=== {{S|verbe|en}} ===\n# [[bavarder|Bavarder]].\n",
+        &FRENCH,
+    );
+    assert_eq!(
+        meanings,
+        vec![
+            triple("French", "Noun", "''(Zoologie)'' [[mammifère|Mammifère]] carnivore."),
+            triple("English", "Verb", "[[bavarder|Bavarder]]."),
+        ]
+    );
+    assert_eq!(relations, vec![triple("French", "synonym", "matou")]);
+}
+
+#[test]
+fn german() {
+    let (meanings, relations) = parse(
+        "== Hund ({{Sprache|Deutsch}}) ==\n=== {{Wortart|Substantiv|Deutsch}}, {{m}} ===\n\
+         {{Worttrennung}}\n:Hund, {{Pl.}} Hun·de\n\
+         {{Bedeutungen}}\n:[1] ein [[Haustier]]\n:[2] ''abwertend:'' ein [[Mensch]]\n\
+         {{Herkunft}}\n:[[mittelhochdeutsch]] ''hunt''\n\
+         {{Synonyme}}\n:[1] [[Köter]], [[Töle]]\n\
+         {{Beispiele}}\n:[1] Der Hund bellt.\n",
+        &GERMAN,
+    );
+    assert_eq!(
+        meanings,
+        vec![
+            triple("German", "Noun", "ein [[Haustier]]"),
+            triple("German", "Noun", "''abwertend:'' ein [[Mensch]]"),
+        ]
+    );
+    assert_eq!(
+        relations,
+        vec![
+            triple("German", "synonym", "Köter"),
+            triple("German", "synonym", "Töle"),
+        ]
+    );
+}
+
+#[test]
+fn japanese() {
+    let (meanings, relations) = parse(
+        "=={{L|ja}}==\n==={{noun}}===\n# [[ネコ]]科の動物。\n\n===類義語===\n*[[にゃんこ]]\n\n\
+         ==英語==\n===名詞===\n# 猫。\n",
+        &JAPANESE,
+    );
+    assert_eq!(
+        meanings,
+        vec![
+            triple("Japanese", "Noun", "[[ネコ]]科の動物。"),
+            triple("English", "Noun", "猫。"),
+        ]
+    );
+    assert_eq!(relations, vec![triple("Japanese", "synonym", "にゃんこ")]);
+}
+
+#[test]
+fn russian() {
+    let (meanings, relations) = parse(
+        "= {{-ru-}} =\n=== Морфологические и синтаксические свойства ===\n\
+         {{сущ ru m a 1a\n|основа=кот\n}}\n\
+         === Семантические свойства ===\n==== Значение ====\n# [[самец]] [[кошка|кошки]]\n\
+         #: {{пример|Кот спит.}}\n\n==== Синонимы ====\n# [[котяра]]\n\n\
+         = {{-en-}} =\n=== Морфологические и синтаксические свойства ===\n{{гл en}}\n\
+         ==== Значение ====\n# [[ловить]]\n",
+        &RUSSIAN,
+    );
+    assert_eq!(
+        meanings,
+        vec![
+            triple("Russian", "Noun", "[[самец]] [[кошка|кошки]]"),
+            triple("English", "Verb", "[[ловить]]"),
+        ]
+    );
+    assert_eq!(relations, vec![triple("Russian", "synonym", "котяра")]);
+}
+
+#[test]
+fn finds_editions() {
+    assert_eq!(Edition::find("fr").map(|edition| edition.code), Some("fr"));
+    assert!(Edition::find("xx").is_none());
+}
+
+// The (relation, target, sense) of each relation on an English page.
+fn relations(text: &str) -> Vec<(String, String, Option<String>)> {
+    parse_wikitext(text, &ENGLISH)
+        .1
+        .into_iter()
+        .map(|r| (r.relation, r.target, r.sense))
+        .collect()
+}
+
+fn relation(relation: &str, target: &str, sense: Option<&str>) -> (String, String, Option<String>) {
+    (relation.to_owned(), target.to_owned(), sense.map(str::to_owned))
+}
+
+#[test]
+fn relation_sections() {
+    let relations = relations(
+        "==English==\n===Noun===\n# A [[feline]].\n\n\
+         ====Synonyms====\n* [[moggy]], [[puss]]\n* [[Thesaurus:cat]]\n\n\
+         ====Antonyms====\n* {{l|en|dog}}\n\n\
+         ====Hypernyms====\n* {{l|en|[[feline]] [[animal]]}}\n\n\
+         ===Etymology===\n* [[not a relation]]\n",
+    );
+    assert_eq!(
+        relations,
+        vec![
+            relation("synonym", "moggy", None),
+            relation("synonym", "puss", None),
+            relation("antonym", "dog", None),
+            relation("hypernym", "feline", None),
+            relation("hypernym", "animal", None),
+        ]
+    );
+}
+
+#[test]
+fn inline_relation_templates() {
+    let relations = relations(
+        "==English==\n===Noun===\n# A [[feline]].\n#: {{syn|en|kitty|puss<q:informal>|q1=rare}}\n\
+         #: {{ant|en|dog}}\n# A [[whip]].\n#: {{hyper|en|whip}}\n",
+    );
+    assert_eq!(
+        relations,
+        vec![
+            relation("synonym", "kitty", Some("A [[feline]].")),
+            relation("synonym", "puss", Some("A [[feline]].")),
+            relation("antonym", "dog", Some("A [[feline]].")),
+            relation("hypernym", "whip", Some("A [[whip]].")),
+        ]
+    );
+}
+
+#[test]
+fn relation_senses() {
+    let relations = relations(
+        "==English==\n===Noun===\n# A [[feline]].\n\n\
+         ====Synonyms====\n* {{sense|animal}} {{l|en|kitty}}, [[puss]]\n\
+         * {{s|person}} {{l|en|guy}}\n",
+    );
+    assert_eq!(
+        relations,
+        vec![
+            relation("synonym", "kitty", Some("animal")),
+            relation("synonym", "puss", Some("animal")),
+            relation("synonym", "guy", Some("person")),
+        ]
+    );
+}
+
+#[test]
+fn column_templates() {
+    let relations = relations(
+        "==English==\n===Noun===\n# A [[feline]].\n\n\
+         ====Derived terms====\n{{col3|en|catbird|catfish<t:a fish>|title=cats}}\n\
+         {{der3|en|[[cat flap]]|catnap}}\n\n\
+         ====Related terms====\n* {{col-auto|en|kitten}}\n",
+    );
+    assert_eq!(
+        relations,
+        vec![
+            relation("derived", "catbird", None),
+            relation("derived", "catfish", None),
+            relation("derived", "cat flap", None),
+            relation("derived", "catnap", None),
+            relation("related", "kitten", None),
+        ]
+    );
+}
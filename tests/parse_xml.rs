@@ -0,0 +1,133 @@
+extern crate define3;
+extern crate quick_xml;
+
+use define3::parse_xml::{parse_page, PageBuffers};
+use define3::{Error, Page};
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+// Parses the first page in `xml`.
+fn parse(xml: &str) -> define3::Result<Option<Page>> {
+    let mut reader = Reader::from_reader(xml.as_bytes());
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event(&mut buf).unwrap() {
+            Event::Start(ref e) if e.name() == b"page" => break,
+            Event::Eof => panic!("no page in {}", xml),
+            _ => (),
+        }
+        buf.clear();
+    }
+    let mut bufs = PageBuffers::default();
+    parse_page(&mut reader, &mut bufs).map(|page| page.map(|page| page.into_owned()))
+}
+
+#[test]
+fn reads_ids_and_text() {
+    let page = parse(
+        "<page><title>cat</title><ns>0</ns><id>12</id>\
+         <revision><id>345</id><contributor><username>x</username><id>6</id></contributor>\
+         <text bytes=\"20\" xml:space=\"preserve\">==English==\n# [[feline]]</text></revision></page>",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(page.title, "cat");
+    assert_eq!(page.id, Some(12));
+    assert_eq!(page.revision_id, Some(345));
+    assert_eq!(page.redirect, None);
+    assert_eq!(page.content, "==English==\n# [[feline]]");
+}
+
+#[test]
+fn keeps_the_newest_revision() {
+    let page = parse(
+        "<page><title>cat</title><ns>0</ns><id>12</id>\
+         <revision><id>100</id><contributor><id>7</id></contributor><text>old</text></revision>\
+         <revision><id>200</id><contributor><id>9</id></contributor><text>new</text></revision>\
+         <revision><id>150</id><contributor><id>8</id></contributor><text>older</text></revision></page>",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(page.id, Some(12));
+    assert_eq!(page.revision_id, Some(200));
+    assert_eq!(page.content, "new");
+}
+
+#[test]
+fn unescapes_text() {
+    let page = parse(
+        "<page><title>Q&amp;A</title><revision><id>1</id>\
+         <text>{{l|en|a &lt;b&gt;}} &quot;c&quot; &amp; d</text></revision></page>",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(page.title, "Q&A");
+    assert_eq!(page.content, "{{l|en|a <b>}} \"c\" & d");
+}
+
+#[test]
+fn joins_text_split_by_cdata() {
+    let page = parse(
+        "<page><title>x</title><revision><id>1</id>\
+         <text>one &amp; <![CDATA[<two> & ]]>three</text></revision></page>",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(page.content, "one & <two> & three");
+
+    let page = parse(
+        "<page><title>x</title><revision><id>1</id>\
+         <text><![CDATA[only cdata]]></text></revision></page>",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(page.content, "only cdata");
+}
+
+#[test]
+fn empty_and_deleted_text() {
+    for text in &[
+        "<text />",
+        "<text bytes=\"0\"></text>",
+        "<text deleted=\"deleted\" />",
+        "<text deleted=\"\">hidden</text>",
+    ] {
+        let page = parse(&format!(
+            "<page><title>x</title><id>1</id><revision><id>2</id>{}</revision></page>",
+            text
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(page.content, "", "{}", text);
+        assert_eq!(page.revision_id, Some(2), "{}", text);
+    }
+}
+
+#[test]
+fn reads_redirects() {
+    let page = parse(
+        "<page><title>Cat</title><redirect title=\"cat &amp; dog\" /><revision><id>1</id>\
+         <text>#REDIRECT [[cat &amp; dog]]</text></revision></page>",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(page.redirect.as_deref(), Some("cat & dog"));
+}
+
+#[test]
+fn pages_without_titles() {
+    assert!(parse("<page><revision><text>x</text></revision></page>").unwrap().is_none());
+}
+
+#[test]
+fn malformed_pages() {
+    match parse("<page><title>x</title><revision><text>&bogus;</text></revision></page>") {
+        Err(Error::MalformedPage { title, .. }) => assert_eq!(title.as_deref(), Some("x")),
+        other => panic!("expected a malformed page, got {:?}", other),
+    }
+    match parse("<page><title>x</title><revision><text>unfinished") {
+        Err(Error::Xml { .. }) => (),
+        other => panic!("expected an XML error, got {:?}", other),
+    }
+}
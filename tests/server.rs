@@ -0,0 +1,233 @@
+extern crate define3;
+extern crate rusqlite;
+extern crate serde_json;
+
+use define3::dictionary::create_tables;
+use define3::server::{handle, Response};
+use define3::templates::Expander;
+use define3::Dictionary;
+
+use rusqlite::Connection;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+// Builds a small database with build_definitions_db's tables.
+fn fixture(name: &str) -> PathBuf {
+    let mut path = env::temp_dir();
+    path.push(format!("define3-test-{}-{}.sqlite3", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    let conn = Connection::open(&path).unwrap();
+    create_tables(&conn).unwrap();
+    conn.execute_batch(
+        "INSERT INTO sources VALUES (1, 'enwikt', 'en', '20240101', '');
+         INSERT INTO sources VALUES (2, 'frwikt', 'fr', '20240301', '');
+         INSERT INTO languages VALUES (1, 'English', 'en', 'Germanic', 'Latn');
+         INSERT INTO languages VALUES (2, 'French', 'fr', 'Romance', 'Latn');
+         INSERT INTO pos VALUES (1, 'Noun');
+         INSERT INTO pos VALUES (2, 'Verb');
+         INSERT INTO entries VALUES (1, 1, 'cat', 1, 10);
+         INSERT INTO entries VALUES (2, 1, 'catch', 2, 11);
+         INSERT INTO entries VALUES (3, 1, 'dog', 3, 12);
+         INSERT INTO entries VALUES (4, 2, 'dog', 5, 14);
+         INSERT INTO senses VALUES (1, 1, 1, 1, 'A small domesticated [[feline]] animal.');
+         INSERT INTO senses VALUES (2, 1, 1, 2, '{{lb|en|nautical}} To hoist the anchor.');
+         INSERT INTO senses VALUES (3, 1, 2, 1, '{{alternative form of|chat}}');
+         INSERT INTO senses VALUES (4, 2, 1, 2, 'To capture.');
+         INSERT INTO senses VALUES (5, 3, 1, 1, 'A <canine> & friend.');
+         INSERT INTO senses VALUES (6, 4, 1, 1, 'Chien.');
+         INSERT INTO relations VALUES (1, 1, 'synonym', 'kitty', 'animal');
+         INSERT INTO redirects VALUES (1, 'Cat', 'cat', 4, 13);",
+    ).unwrap();
+    path
+}
+
+fn get(name: &str, url: &str, accept: Option<&str>) -> Response {
+    let path = fixture(name);
+    let dict = Dictionary::open(&path).unwrap();
+    let response = handle(&dict, &Expander::new(), "GET", url, accept);
+    let _ = fs::remove_file(&path);
+    response
+}
+
+#[test]
+fn define_json() {
+    let response = get("define_json", "/define/cat?related", None);
+    assert_eq!(response.status, 200);
+    assert!(response.content_type.starts_with("application/json"));
+    let json: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+    assert_eq!(json["word"], "cat");
+    assert_eq!(json["languages"][0]["language"], "English");
+    assert_eq!(
+        json["languages"][0]["parts_of_speech"][1]["definitions"][0],
+        "(nautical) To hoist the anchor."
+    );
+    assert_eq!(json["languages"][0]["relations"][0]["target"], "kitty");
+    assert_eq!(
+        json["languages"][1]["parts_of_speech"][0]["definitions"][0],
+        "Alternative form of chat"
+    );
+}
+
+#[test]
+fn define_json_without_relations() {
+    let response = get("define_json_without_relations", "/define/cat", None);
+    let json: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+    assert!(json["languages"][0].get("relations").is_none());
+}
+
+#[test]
+fn define_filters_languages() {
+    let response = get("define_filters_languages", "/define/cat?lang=French", None);
+    let json: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+    assert_eq!(json["languages"].as_array().unwrap().len(), 1);
+    assert_eq!(json["languages"][0]["language"], "French");
+}
+
+#[test]
+fn define_filters_parts_of_speech() {
+    let response = get("define_filters_parts_of_speech", "/define/cat?pos=v", None);
+    let json: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+    assert_eq!(json["languages"].as_array().unwrap().len(), 1);
+    let poses = json["languages"][0]["parts_of_speech"].as_array().unwrap();
+    assert_eq!(poses.len(), 1);
+    assert_eq!(poses[0]["part_of_speech"], "Verb");
+
+    let response = get("define_unknown_part_of_speech", "/define/cat?pos=nope", None);
+    assert_eq!(response.status, 400);
+}
+
+#[test]
+fn define_filters_sources() {
+    let response = get("define_all_sources", "/define/dog", None);
+    let json: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+    let definitions = &json["languages"][0]["parts_of_speech"][0]["definitions"];
+    assert_eq!(definitions.as_array().unwrap().len(), 2);
+
+    let response = get("define_filters_sources", "/define/dog?source=FRWIKT", None);
+    let json: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+    let definitions = &json["languages"][0]["parts_of_speech"][0]["definitions"];
+    assert_eq!(definitions.as_array().unwrap().len(), 1);
+    assert_eq!(definitions[0], "Chien.");
+
+    let response = get("define_unknown_source", "/define/dog?source=nope", None);
+    assert_eq!(response.status, 400);
+}
+
+#[test]
+fn define_html() {
+    let response = get("define_html", "/define/cat", Some("text/html,*/*"));
+    assert_eq!(response.status, 200);
+    assert!(response.content_type.starts_with("text/html"));
+    assert!(response.body.contains("<h2 id=\"French\">French</h2>"));
+    assert!(response.body.contains("<a href=\"/define/feline?format=html\">feline</a>"));
+
+    let response = get("define_html_escapes", "/define/dog?format=html", None);
+    assert!(response.body.contains("A &lt;canine&gt; &amp; friend."));
+}
+
+#[test]
+fn define_follows_redirects() {
+    let response = get("define_follows_redirects", "/define/Cat", None);
+    assert_eq!(response.status, 200);
+    let json: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+    assert_eq!(json["word"], "cat");
+    assert_eq!(json["languages"][0]["language"], "English");
+
+    let response = get("define_follows_redirects_html", "/define/Cat?format=html", None);
+    assert!(response.body.contains("<h1>cat</h1>"));
+    assert!(response.body.contains("<p>Cat → cat</p>"));
+}
+
+#[test]
+fn define_prefers_entries_to_redirects() {
+    let path = fixture("define_prefers_entries");
+    Connection::open(&path)
+        .unwrap()
+        .execute_batch(
+            "INSERT INTO entries VALUES (5, 2, 'Cat', 6, 15);
+             INSERT INTO senses VALUES (7, 5, 2, 1, 'Nom de famille.');",
+        )
+        .unwrap();
+    let dict = Dictionary::open(&path).unwrap();
+    let define = |url| {
+        let response = handle(&dict, &Expander::new(), "GET", url, None);
+        serde_json::from_str::<serde_json::Value>(&response.body).unwrap()
+    };
+
+    // frwikt's entry isn't hidden behind enwikt's redirect
+    let json = define("/define/Cat");
+    assert_eq!(json["word"], "Cat");
+    assert_eq!(json["languages"][0]["language"], "French");
+    assert_eq!(json["languages"][0]["parts_of_speech"][0]["definitions"][0], "Nom de famille.");
+
+    // without frwikt, the redirect is followed
+    let json = define("/define/Cat?source=enwikt");
+    assert_eq!(json["word"], "cat");
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn define_missing_word() {
+    let response = get("define_missing_word", "/define/no%20such%20word", None);
+    assert_eq!(response.status, 404);
+    let json: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+    assert_eq!(json["word"], "no such word");
+    assert_eq!(json["languages"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn search() {
+    let response = get("search", "/search?q=ca&limit=10", None);
+    assert_eq!(response.status, 200);
+    let json: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+    assert_eq!(json["results"], serde_json::json!(["cat", "catch"]));
+
+    let response = get("search_without_query", "/search", None);
+    assert_eq!(response.status, 400);
+}
+
+#[test]
+fn random() {
+    let response = get("random", "/random", None);
+    assert_eq!(response.status, 302);
+    let location = response.location.unwrap();
+    assert!(["/define/cat", "/define/catch", "/define/dog"].contains(&location.as_str()));
+}
+
+#[test]
+fn random_skips_words_without_definitions() {
+    let path = fixture("random_skips");
+    Connection::open(&path)
+        .unwrap()
+        .execute_batch("DELETE FROM senses WHERE entry_id != 1;")
+        .unwrap();
+    let dict = Dictionary::open(&path).unwrap();
+    for _ in 0..20 {
+        assert_eq!(dict.random_word().unwrap().as_deref(), Some("cat"));
+    }
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn unknown_paths_and_methods() {
+    assert_eq!(get("unknown_path", "/nope", None).status, 404);
+    let path = fixture("unknown_method");
+    let dict = Dictionary::open(&path).unwrap();
+    assert_eq!(handle(&dict, &Expander::new(), "POST", "/define/cat", None).status, 405);
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn refuses_incompatible_database() {
+    let mut path = env::temp_dir();
+    path.push(format!("define3-test-old-schema-{}.sqlite3", std::process::id()));
+    let _ = fs::remove_file(&path);
+    Connection::open(&path)
+        .unwrap()
+        .execute_batch("CREATE TABLE words (name text not null);")
+        .unwrap();
+    let error = Dictionary::open(&path).err().unwrap();
+    let _ = fs::remove_file(&path);
+    assert!(error.to_string().contains("schema version 1"));
+}